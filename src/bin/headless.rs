@@ -0,0 +1,81 @@
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::log::{Level, LogPlugin};
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use demo::cli::CliArgs;
+use demo::galaxy::Galaxy;
+use demo::galaxy_event::config::GalaxyEventConfig;
+use demo::headless::*;
+use demo::orchestrator::polling::StatePolling;
+use demo::orchestrator::record::RecordPath;
+use demo::replay::ReplaySchedule;
+use demo::resources::SimulationSeed;
+use std::time::Duration;
+
+const DEFAULT_GALAXY: &str = "assets/galaxies/playing.galaxy.ron";
+
+// Runs the galaxy without a window as fast as possible, one fixed tick per frame
+fn main() -> AppExit {
+    let args = match CliArgs::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            return AppExit::error();
+        }
+    };
+    let seed = SimulationSeed(args.seed.unwrap_or_else(rand::random));
+    let galaxy_path = args.galaxy.unwrap_or_else(|| DEFAULT_GALAXY.to_string());
+    let galaxy = match Galaxy::from_file(&galaxy_path) {
+        Ok(galaxy) => galaxy,
+        Err(e) => {
+            eprintln!("{galaxy_path}: {e}");
+            return AppExit::error();
+        }
+    };
+
+    let mut app = App::new();
+    if let Some(path) = args.record {
+        app.insert_resource(RecordPath(path.into()));
+    }
+    if let Some(secs) = args.poll {
+        app.insert_resource(StatePolling::every(secs));
+    }
+    if let Some(path) = args.events {
+        match GalaxyEventConfig::from_file(&path) {
+            Ok(config) => {
+                app.insert_resource(config);
+            }
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                return AppExit::error();
+            }
+        }
+    }
+    if let Some(path) = args.replay {
+        match ReplaySchedule::from_file(&path) {
+            Ok(schedule) => {
+                app.insert_resource(schedule);
+            }
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                return AppExit::error();
+            }
+        }
+    }
+
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::ZERO)),
+        LogPlugin {
+            level: Level::WARN,
+            ..default()
+        },
+    ))
+    .insert_resource(TimeUpdateStrategy::ManualDuration(
+        Time::<Fixed>::default().timestep(),
+    ))
+    .insert_resource(seed)
+    .insert_resource(HeadlessGalaxy(galaxy))
+    .insert_resource(HeadlessRun::new(args.ticks.unwrap_or(DEFAULT_TICKS)))
+    .add_plugins(headless_plugin)
+    .run()
+}
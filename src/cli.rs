@@ -0,0 +1,98 @@
+use crate::explorer::strategy::StrategyKind;
+
+// Command line flags, parsed by hand before the App is built
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub seed: Option<u64>,
+    pub ticks: Option<u64>,
+    pub galaxy: Option<String>,
+    pub record: Option<String>,
+    pub replay: Option<String>,
+    pub scenario: Option<String>,
+    pub events: Option<String>,
+    pub poll: Option<f32>,
+    // One explorer per flag, a single manual explorer when empty
+    pub explorers: Vec<StrategyKind>,
+}
+
+impl CliArgs {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    // A run must never silently differ from the one asked for, so any bad flag is an error
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => match args.next().map(|value| value.parse::<u64>()) {
+                    Some(Ok(seed)) => cli.seed = Some(seed),
+                    _ => return Err("--seed expects an unsigned integer".to_string()),
+                },
+                "--ticks" => match args.next().map(|value| value.parse::<u64>()) {
+                    Some(Ok(ticks)) => cli.ticks = Some(ticks),
+                    _ => return Err("--ticks expects an unsigned integer".to_string()),
+                },
+                "--galaxy" => match args.next() {
+                    Some(path) => cli.galaxy = Some(path),
+                    None => return Err("--galaxy expects a path to a .galaxy.ron file".to_string()),
+                },
+                "--record" => match args.next() {
+                    Some(path) => cli.record = Some(path),
+                    None => return Err("--record expects a path to write the recording to".to_string()),
+                },
+                "--replay" => match args.next() {
+                    Some(path) => cli.replay = Some(path),
+                    None => return Err("--replay expects a path to a recording".to_string()),
+                },
+                "--scenario" => match args.next() {
+                    Some(path) => cli.scenario = Some(path),
+                    None => return Err("--scenario expects a path to a scenario script".to_string()),
+                },
+                "--events" => match args.next() {
+                    Some(path) => cli.events = Some(path),
+                    None => return Err("--events expects a path to a galaxy event config".to_string()),
+                },
+                "--poll" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(secs)) if secs > 0.0 => cli.poll = Some(secs),
+                    _ => return Err("--poll expects a positive number of seconds".to_string()),
+                },
+                "--explorer" => match args.next().map(|value| value.parse::<StrategyKind>()) {
+                    Some(Ok(strategy)) => cli.explorers.push(strategy),
+                    Some(Err(e)) => return Err(format!("--explorer: {e}")),
+                    None => return Err("--explorer expects manual, round-robin, charged-cells or recipe:<resource>".to_string()),
+                },
+                other => return Err(format!("unknown argument {other}")),
+            }
+        }
+        Ok(cli)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_flags_and_rejects_bad_values() {
+        let cli = parse(&["--seed", "42", "--ticks", "10", "--poll", "0.5"]).unwrap();
+        assert_eq!(cli.seed, Some(42));
+        assert_eq!(cli.ticks, Some(10));
+        assert_eq!(cli.poll, Some(0.5));
+
+        for args in [
+            &["--seed", "4x2"][..],
+            &["--seed"],
+            &["--ticks", "-1"],
+            &["--poll", "0"],
+            &["--sed", "42"],
+        ] {
+            assert!(parse(args).is_err(), "{args:?} should be rejected");
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::GameState;
+use crate::log::LogEvent;
+use crate::orchestrator::Orchestrator;
+use crate::theme;
+use bevy::app::FixedMain;
+use bevy::prelude::*;
+use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
+
+const SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+const NORMAL_SPEED: usize = 1;
+
+// Pause and speed of the Playing galaxy, it drives Time<Virtual> so every timer follows it
+#[derive(Resource)]
+pub struct SimulationClock {
+    pub paused: bool,
+    speed: usize,
+    // Set by the step key, the next step_once runs a single fixed tick
+    stepping: bool,
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: NORMAL_SPEED,
+            stepping: false,
+        }
+    }
+}
+
+impl SimulationClock {
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn status(&self) -> String {
+        format!(
+            "{} {}x (Space pause, - and = speed, . step)",
+            if self.paused { "Paused" } else { "Running" },
+            self.speed()
+        )
+    }
+}
+
+#[derive(Component)]
+pub struct ClockStatus;
+
+pub fn clock_plugin(app: &mut App) {
+    app.init_resource::<SimulationClock>()
+        .add_systems(OnEnter(GameState::Playing), (reset_clock, setup).chain())
+        .add_systems(OnExit(GameState::Playing), reset_clock)
+        .add_systems(
+            Update,
+            (
+                clock_controls_system,
+                step_once.run_if(|clock: Res<SimulationClock>| clock.stepping),
+                update_clock_status,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, clock: Res<SimulationClock>) {
+    commands.spawn((
+        DespawnOnExit(GameState::Playing),
+        Node {
+            position_type: PositionType::Absolute,
+            left: percent(35.0),
+            top: px(10.0),
+            ..default()
+        },
+        Text::new(clock.status()),
+        theme::basic_font(&asset_server),
+        theme::text_color(),
+        ClockStatus,
+    ));
+}
+
+fn reset_clock(mut clock: ResMut<SimulationClock>, mut time: ResMut<Time<Virtual>>) {
+    *clock = SimulationClock::default();
+    time.unpause();
+    time.set_relative_speed(clock.speed());
+}
+
+// Planets keep running on their own threads, they are stopped with the clock.
+// Returns the errors to log
+fn send_to_every_planet(
+    orch: &mut Orchestrator,
+    msg: fn() -> OrchestratorToPlanet,
+) -> Vec<LogEvent> {
+    orch.planet_ids()
+        .into_iter()
+        .filter_map(|id| orch.send_to_planet_id(id, msg()).err())
+        .map(|e| LogEvent::error(e.to_string()))
+        .collect()
+}
+
+fn clock_controls_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut clock: ResMut<SimulationClock>,
+    mut time: ResMut<Time<Virtual>>,
+    orch: Option<ResMut<Orchestrator>>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    let Some(mut orch) = orch else {
+        return;
+    };
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        clock.paused = !clock.paused;
+        clock.stepping = false;
+        if clock.paused {
+            time.pause();
+            log.write_batch(send_to_every_planet(&mut orch, || {
+                OrchestratorToPlanet::StopPlanetAI
+            }));
+            log.write(LogEvent::info("Simulation paused"));
+        } else {
+            time.unpause();
+            log.write_batch(send_to_every_planet(&mut orch, || {
+                OrchestratorToPlanet::StartPlanetAI
+            }));
+            log.write(LogEvent::info("Simulation resumed"));
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Minus) {
+        clock.speed = clock.speed.saturating_sub(1);
+        time.set_relative_speed(clock.speed());
+    }
+    if keyboard_input.just_pressed(KeyCode::Equal) {
+        clock.speed = (clock.speed + 1).min(SPEEDS.len() - 1);
+        time.set_relative_speed(clock.speed());
+    }
+    if keyboard_input.just_pressed(KeyCode::Period) && clock.paused {
+        clock.stepping = true;
+    }
+}
+
+// Runs FixedMain exactly once while Time<Virtual> stays paused, the same way
+// run_fixed_main_schedule does for each elapsed timestep. The planets are woken up
+// around that tick only, so they answer what it delivers and report their state
+// before being stopped again
+fn step_once(world: &mut World) {
+    world.resource_mut::<SimulationClock>().stepping = false;
+    if !world.contains_resource::<Orchestrator>() {
+        return;
+    }
+    world.resource_scope(|world, mut orch: Mut<Orchestrator>| {
+        let errors = send_to_every_planet(&mut orch, || OrchestratorToPlanet::StartPlanetAI);
+        world.write_message_batch(errors);
+    });
+
+    let timestep = world.resource::<Time<Fixed>>().timestep();
+    world.resource_mut::<Time<Fixed>>().advance_by(timestep);
+    *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+    world.run_schedule(FixedMain);
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+
+    world.resource_scope(|world, mut orch: Mut<Orchestrator>| {
+        let mut errors =
+            send_to_every_planet(&mut orch, || OrchestratorToPlanet::InternalStateRequest);
+        errors.extend(send_to_every_planet(&mut orch, || {
+            OrchestratorToPlanet::StopPlanetAI
+        }));
+        world.write_message_batch(errors);
+    });
+}
+
+fn update_clock_status(
+    clock: Res<SimulationClock>,
+    mut status: Single<&mut Text, With<ClockStatus>>,
+) {
+    let text = clock.status();
+    if status.0 != text {
+        status.0 = text;
+    }
+}
@@ -1,190 +1,532 @@
 use super::GameState;
+use crate::explorer::dialog::create_button;
+use crate::galaxy::*;
+use crate::galaxy_event::config::GalaxyEventConfig;
+use crate::galaxy_event::trajectory::{galaxy_edge, launch_asteroid};
 use crate::galaxy_event::*;
+use crate::log::*;
 use crate::orchestrator::Orchestrator;
+use crate::orchestrator::factory::{DEFAULT_PLANET_AI, PlanetRegistry};
+use crate::orchestrator::lifecycle::*;
+use crate::orchestrator::pending::*;
+use crate::orchestrator::record::RecordPath;
 use crate::planet::*;
+use crate::resources::{SimulationRng, SimulationSeed};
+use crate::scenario::*;
 use crate::simulation_better::*;
+use crate::stats::{GalaxyStats, PlanetStats};
 use crate::theme;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
-use common_game::protocols::orchestrator_planet::PlanetToOrchestrator;
-use crossbeam_channel::unbounded;
+
+const BURST_SIZE: usize = 10;
+// Time between two events of a burst reaching the planet
+const BURST_INTERVAL_SECS: f32 = 0.4;
+const PLANET_SPRITES: [&str; 4] = [
+    "sprites/Ice.png",
+    "sprites/Terran.png",
+    "sprites/Lava.png",
+    "sprites/Baren.png",
+];
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum CreativeAction {
+    Sunray,
+    Asteroid,
+    SunrayBurst,
+    AsteroidBurst,
+    SpawnPlanet,
+    KillPlanet,
+}
+
+impl CreativeAction {
+    const ALL: [CreativeAction; 6] = [
+        CreativeAction::Sunray,
+        CreativeAction::Asteroid,
+        CreativeAction::SunrayBurst,
+        CreativeAction::AsteroidBurst,
+        CreativeAction::SpawnPlanet,
+        CreativeAction::KillPlanet,
+    ];
+
+    fn label(self) -> String {
+        match self {
+            CreativeAction::Sunray => "Sunray (S)".to_string(),
+            CreativeAction::Asteroid => "Asteroid (A)".to_string(),
+            CreativeAction::SunrayBurst => format!("{BURST_SIZE} sunrays"),
+            CreativeAction::AsteroidBurst => format!("{BURST_SIZE} asteroids"),
+            CreativeAction::SpawnPlanet => "Spawn planet (P)".to_string(),
+            CreativeAction::KillPlanet => "Kill planet (K)".to_string(),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Sandbox {
+    // Clicked planet, the one with the lowest id when unset or gone
+    target: Option<Entity>,
+    // The next click on empty space places a new planet
+    placing: bool,
+    next_id: u32,
+}
 
 #[derive(Component)]
-struct SettingsDialog;
+struct TargetLabel;
 
 pub fn creative_plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::Creative), setup)
+    app.init_resource::<Sandbox>()
+        .add_systems(OnEnter(GameState::Creative), setup)
+        .add_systems(
+            FixedUpdate,
+            (
+                run_scenario_system.run_if(resource_exists::<ScenarioSchedule>),
+                (
+                    crate::galaxy_event::event_handler_system,
+                    crate::galaxy_event::trajectory::asteroid_motion_system,
+                    crate::galaxy_event::cleanup_events_system,
+                )
+                    .chain()
+                    .run_if(scenario_running),
+            )
+                .chain()
+                .run_if(in_state(GameState::Creative)),
+        )
         .add_systems(
             Update,
             (
-                create_event_listen,
-                crate::galaxy_event::event_visual_move,
-                crate::galaxy_event::event_handler_system,
+                creative_action_system,
+                click_in_sandbox,
+                highlight_target,
+                (scenario_controls_system, update_scenario_status)
+                    .chain()
+                    .run_if(resource_exists::<ScenarioSchedule>),
+                crate::galaxy_event::event_visual_move.run_if(scenario_running),
+                crate::orchestrator::planet_backlog_diagnostic,
                 listen_to_planets,
-                crate::galaxy_event::cleanup_events_system,
+                expire_pending_requests,
+                despawn_destroyed_planets,
+                join_killed_planets,
             )
                 .chain()
                 .run_if(in_state(GameState::Creative)),
         )
         .add_systems(
             PostUpdate,
-            (
-                check_entities_and_end_game,
-                update_planet_cell,
-                update_planet_rocket,
-            )
+            (leave_sandbox, update_planet_cell, update_planet_rocket)
                 .run_if(in_state(GameState::Creative)),
         )
-        .add_observer(event_visual_spawn);
+        .add_observer(event_visual_spawn)
+        .add_observer(crate::galaxy_event::trajectory::asteroid_body_visual);
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let mut orchestrator = Orchestrator::new();
-    let mut id = 0;
-
-    let (orch_tx, orch_rx) = unbounded();
-    let (planet_tx, planet_rx) = unbounded();
-    let (_expl_tx, expl_rx) = unbounded();
-    orchestrator.add_op_tx(id, orch_tx);
-    orchestrator.add_po_rx(id, planet_rx);
-    // Planets
-    let mut p1 = trip::trip(id, orch_rx.clone(), planet_tx.clone(), expl_rx.clone())
-        .expect("Error createing planet1");
-    let planet1 = commands
-        .spawn(planet(
-            id,
-            "Alpha",
-            Vec3::new(0.0, 0.0, 0.0),
-            asset_server.load("sprites/Ice.png"),
-        ))
-        .id();
-    let p1_handle = std::thread::spawn(move || {
-        let _ = p1.run();
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    galaxies: Res<Assets<Galaxy>>,
+    galaxy_handles: Res<GalaxyHandles>,
+    registry: Res<PlanetRegistry>,
+    seed: Res<SimulationSeed>,
+    record: Option<Res<RecordPath>>,
+    scenario: Option<ResMut<ScenarioSchedule>>,
+) {
+    let Some(galaxy) = galaxies.get(galaxy_handles.get(GameState::Creative)) else {
+        error!("Galaxy definition for the Creative state is not loaded");
+        return;
+    };
+    let orchestrator = spawn_galaxy(
+        &mut commands,
+        &asset_server,
+        &registry,
+        galaxy,
+        record.as_deref(),
+        GameState::Creative,
+    );
+
+    commands.spawn(log_screen(GameState::Creative));
+    commands.insert_resource(GalaxyStats::from_galaxy(galaxy));
+    // Asteroid launch points come from it, so a scenario flies the same way on every run
+    commands.insert_resource(SimulationRng::from_seed(*seed));
+    commands.insert_resource(Sandbox {
+        next_id: galaxy.planets.len() as u32,
+        ..default()
+    });
+
+    let buttons = CreativeAction::ALL.map(|action| {
+        create_button(
+            &asset_server,
+            action.label(),
+            action,
+            percent(15.0),
+            px(40.0),
+            px(0.0),
+            px(2.0),
+            Color::WHITE,
+        )
     });
-    orchestrator.add_planet_handle(id, p1_handle);
 
     commands.spawn((
+        DespawnOnExit(GameState::Creative),
         Node {
             flex_direction: FlexDirection::Column,
-            row_gap: percent(5.0),
-            left: px(20),
-            height: percent(90.0),
-            width: percent(20.0),
-            top: percent(5.0),
+            row_gap: px(6.0),
+            width: percent(70.0),
+            left: percent(25.0),
+            top: px(10.0),
             ..default()
         },
-        children![
-            (planet_state(
-                &asset_server,
-                "Alpha",
-                planet1,
-                PlanetCell {
-                    num_cell: 5,
-                    charged_cell: 0,
+        Children::spawn((
+            Spawn((
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: percent(1.0),
+                    ..default()
                 },
-                PlanetRocket(false),
+                Children::spawn(SpawnIter(buttons.into_iter())),
+            )),
+            Spawn((
+                Text::new("Click a planet to target it, Esc goes back to the settings"),
+                theme::basic_font(&asset_server),
+                theme::text_color(),
+                TargetLabel,
             )),
-        ],
+        )),
     ));
 
-    let width = percent(25.0);
-    let height = percent(50.0);
-
-    commands.spawn((
-        DespawnOnExit(GameState::Creative),
-        Node {
-            flex_direction: FlexDirection::Row,
-            column_gap: percent(5.0),
-            width: percent(70.0),
-            height: percent(10.0),
-            left: percent(30),
-            ..default()
-        },
-        children![(
-            Node {
-                width,
-                height,
-                border: UiRect::all(px(2.0)),
-                // horizontally center child text
-                justify_content: JustifyContent::Center,
-                // vertically center child text
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            BorderColor::all(Color::WHITE),
-            children![(
-                Text::new("Sunray (S)"),
-                theme::title_font(&asset_server),
-                theme::text_color(),
-            )],
-        ),(
+    if let Some(mut scenario) = scenario {
+        scenario.restart();
+        scenario.paused = false;
+        commands.write_message(LogEvent::info(format!(
+            "Running a scenario of {} steps",
+            scenario.step_count()
+        )));
+        commands.spawn((
+            DespawnOnExit(GameState::Creative),
             Node {
-                width,
-                height,
-                border: UiRect::all(px(2.0)),
-                // horizontally center child text
-                justify_content: JustifyContent::Center,
-                // vertically center child text
-                align_items: AlignItems::Center,
+                position_type: PositionType::Absolute,
+                left: percent(25.0),
+                bottom: percent(2.0),
                 ..default()
             },
-            BorderColor::all(Color::WHITE),
-            children![(
-                Text::new("Aseroid (A)"),
-                theme::title_font(&asset_server),
-                theme::text_color(),
-            )],
-        )],
-    ));
-
-    for i in 0..=id {
-        orchestrator.send_to_planet_id(i, OrchestratorToPlanet::StartPlanetAI);
-        match orchestrator
-            .recv_from_planet_id(i)
-            .expect("No message received")
-        {
-            PlanetToOrchestrator::StartPlanetAIResult { planet_id } => {
-                info!("Planet {planet_id} started");
-            }
-            _other => panic!("Failed to start planet"),
-        }
+            Text::new(scenario.status()),
+            theme::basic_font(&asset_server),
+            theme::text_color(),
+            ScenarioStatus,
+        ));
     }
 
     commands.insert_resource(orchestrator);
 }
 
-fn create_event_listen(mut commands: Commands, planet: Single<Entity, With<Planet>>, keyboard_input: Res<ButtonInput<KeyCode>>) {
-    if keyboard_input.just_pressed(KeyCode::KeyS) {
+fn current_target<'a>(
+    sandbox: &Sandbox,
+    planet_query: &'a Query<(Entity, &PlanetId, &Name), With<Planet>>,
+) -> Option<(Entity, &'a PlanetId, &'a Name)> {
+    sandbox
+        .target
+        .and_then(|target| planet_query.get(target).ok())
+        .or_else(|| planet_query.iter().min_by_key(|(_, id, _)| id.0))
+}
+
+// Sunrays reach the planet after the travel time, one burst event every BURST_INTERVAL_SECS
+fn spawn_sunrays(commands: &mut Commands, planet: Entity, travel_secs: f32, count: usize) {
+    for i in 0..count {
         commands.spawn((
             DespawnOnExit(GameState::Creative),
             GalaxyEvent::Sunray,
             EventTarget {
-                planet: *planet,
-                duration: Timer::from_seconds(3.0, TimerMode::Once),
+                planet,
+                duration: Timer::from_seconds(
+                    travel_secs + i as f32 * BURST_INTERVAL_SECS,
+                    TimerMode::Once,
+                ),
             },
         ));
     }
+}
 
-    if keyboard_input.just_pressed(KeyCode::KeyA) {
-        commands.spawn((
-            DespawnOnExit(GameState::Creative),
-            GalaxyEvent::Asteroid,
-            EventTarget {
-                planet: *planet,
-                duration: Timer::from_seconds(3.0, TimerMode::Once),
-            },
+// Asteroids are aimed the same way, but only hit the planet if nothing intercepts them
+fn launch_asteroids(
+    commands: &mut Commands,
+    rng: &mut SimulationRng,
+    edge: (Vec2, f32),
+    target: Vec2,
+    travel_secs: f32,
+    count: usize,
+) {
+    for i in 0..count {
+        launch_asteroid(
+            commands,
+            &mut rng.0,
+            edge,
+            target,
+            travel_secs + i as f32 * BURST_INTERVAL_SECS,
+            None,
+            GameState::Creative,
+        );
+    }
+}
+
+fn log_approaching(
+    log: &mut MessageWriter<LogEvent>,
+    planet_id: u32,
+    event: GalaxyEvent,
+    count: usize,
+) {
+    let (kind, message) = match event {
+        GalaxyEvent::Sunray => (LogKind::Sunray, " Sunray approaching!"),
+        GalaxyEvent::Asteroid => (LogKind::Asteroid, " Asteroid approaching!"),
+    };
+    if count == 1 {
+        log.write(LogEvent::planet(kind, planet_id, message));
+    } else {
+        log.write(LogEvent::planet(
+            kind,
+            planet_id,
+            format!("{message} (x{count})"),
         ));
     }
 }
 
-fn check_entities_and_end_game(
-    planet: Query<&Planet>,
-    mut next_state: ResMut<NextState<GameState>>,
+fn kill_planet(
+    commands: &mut Commands,
+    orch: &mut Orchestrator,
+    planet: Entity,
+    planet_id: u32,
+    ui_query: &Query<(Entity, &PlanetUi)>,
+    log: &mut MessageWriter<LogEvent>,
+) {
+    if let Some((ui_entity, _)) = ui_query.iter().find(|&(_, ui)| ui.0 == planet) {
+        commands.entity(ui_entity).despawn();
+    }
+    commands.entity(planet).despawn();
+    // The KillPlanetResult answer joins the thread
+    match orch.send_to_planet_id(planet_id, OrchestratorToPlanet::KillPlanet) {
+        Ok(_) => {
+            log.write(LogEvent::planet(
+                LogKind::Destroyed,
+                planet_id,
+                "was killed from the sandbox",
+            ));
+        }
+        Err(e) => {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+}
+
+fn creative_action_system(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &CreativeAction), Changed<Interaction>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut sandbox: ResMut<Sandbox>,
+    planet_query: Query<(Entity, &PlanetId, &Name), With<Planet>>,
+    transform_query: Query<&Transform, With<Planet>>,
+    ui_query: Query<(Entity, &PlanetUi)>,
+    config: Res<GalaxyEventConfig>,
+    mut rng: ResMut<SimulationRng>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    let mut actions: Vec<CreativeAction> = interaction_query
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(_, action)| *action)
+        .collect();
+    for (key, action) in [
+        (KeyCode::KeyS, CreativeAction::Sunray),
+        (KeyCode::KeyA, CreativeAction::Asteroid),
+        (KeyCode::KeyP, CreativeAction::SpawnPlanet),
+        (KeyCode::KeyK, CreativeAction::KillPlanet),
+    ] {
+        if keyboard_input.just_pressed(key) {
+            actions.push(action);
+        }
+    }
+
+    for action in actions {
+        if action == CreativeAction::SpawnPlanet {
+            sandbox.placing = true;
+            log.write(LogEvent::info(
+                "Click on empty space to place the new planet",
+            ));
+            continue;
+        }
+        let Some((planet, id, _)) = current_target(&sandbox, &planet_query) else {
+            log.write(LogEvent::info("There is no planet to target"));
+            continue;
+        };
+        let (event, count) = match action {
+            CreativeAction::Sunray => (GalaxyEvent::Sunray, 1),
+            CreativeAction::Asteroid => (GalaxyEvent::Asteroid, 1),
+            CreativeAction::SunrayBurst => (GalaxyEvent::Sunray, BURST_SIZE),
+            CreativeAction::AsteroidBurst => (GalaxyEvent::Asteroid, BURST_SIZE),
+            CreativeAction::KillPlanet => {
+                kill_planet(&mut commands, &mut orch, planet, id.0, &ui_query, &mut log);
+                sandbox.target = None;
+                continue;
+            }
+            CreativeAction::SpawnPlanet => continue,
+        };
+        match event {
+            GalaxyEvent::Sunray => spawn_sunrays(&mut commands, planet, config.travel_secs, count),
+            GalaxyEvent::Asteroid => {
+                let Ok(target) = transform_query.get(planet) else {
+                    continue;
+                };
+                let positions: Vec<Vec2> = transform_query
+                    .iter()
+                    .map(|transform| transform.translation.truncate())
+                    .collect();
+                launch_asteroids(
+                    &mut commands,
+                    &mut rng,
+                    galaxy_edge(&positions),
+                    target.translation.truncate(),
+                    config.travel_secs,
+                    count,
+                );
+            }
+        }
+        log_approaching(&mut log, id.0, event, count);
+    }
+}
+
+// Clicks target a planet, or place a new one after Spawn planet
+fn click_in_sandbox(
+    mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    interaction_query: Query<&Interaction>,
+    planet_query: Query<(Entity, &Transform), With<Planet>>,
+    planet_list: Single<Entity, With<PlanetList>>,
+    asset_server: Res<AssetServer>,
+    registry: Res<PlanetRegistry>,
+    mut sandbox: ResMut<Sandbox>,
+    mut stats: ResMut<GalaxyStats>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
 ) {
-    if !planet.is_empty() {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    // Clicks on the UI are for the buttons
+    if interaction_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    let (camera, camera_transform) = *camera;
+    let Some(cursor) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
         return;
+    };
+    let distance_to = |transform: &Transform| transform.translation.truncate().distance(cursor);
+
+    if !sandbox.placing {
+        if let Some((planet, _)) = planet_query
+            .iter()
+            .find(|(_, transform)| distance_to(transform) <= PLANET_RADIUS)
+        {
+            sandbox.target = Some(planet);
+        }
+        return;
+    }
+
+    if planet_query
+        .iter()
+        .any(|(_, transform)| distance_to(transform) < PLANET_RADIUS * 2.0)
+    {
+        log.write(LogEvent::info(
+            "Too close to another planet, click elsewhere",
+        ));
+        return;
+    }
+    sandbox.placing = false;
+    let id = sandbox.next_id;
+    sandbox.next_id += 1;
+    let description = PlanetDescription {
+        name: format!("Planet {id}"),
+        position: (cursor.x, cursor.y),
+        sprite: PLANET_SPRITES[id as usize % PLANET_SPRITES.len()].to_string(),
+        num_cell: 5,
+        ai: DEFAULT_PLANET_AI.to_string(),
+    };
+    if let Err(e) = launch_planet(&mut orch, &registry, id, &description) {
+        log.write(LogEvent::error(format!(
+            "Planet {} could not be created: {e}",
+            description.name
+        )));
+        return;
+    }
+    let planet = spawn_planet_visuals(
+        &mut commands,
+        &asset_server,
+        *planet_list,
+        id,
+        &description,
+        GameState::Creative,
+    );
+    stats.planets.insert(
+        id,
+        PlanetStats {
+            name: description.name.clone(),
+            ai: description.ai.clone(),
+            ..default()
+        },
+    );
+    match orch.send_to_planet_id(id, OrchestratorToPlanet::StartPlanetAI) {
+        Ok(_) => {
+            log.write(LogEvent::planet(
+                LogKind::Info,
+                id,
+                format!("{} joined the sandbox", description.name),
+            ));
+        }
+        Err(e) => {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+    sandbox.target = Some(planet);
+}
+
+fn highlight_target(
+    sandbox: Res<Sandbox>,
+    planet_query: Query<(Entity, &PlanetId, &Name), With<Planet>>,
+    mut sprite_query: Query<(Entity, &mut Sprite), With<Planet>>,
+    mut label: Single<&mut Text, With<TargetLabel>>,
+) {
+    let target = current_target(&sandbox, &planet_query);
+    for (planet, mut sprite) in sprite_query.iter_mut() {
+        let color = if target.is_some_and(|(target, _, _)| target == planet) {
+            theme::color::SELECTED
+        } else {
+            Color::WHITE
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+
+    let text = match (target, sandbox.placing) {
+        (_, true) => "Click on empty space to place the new planet".to_string(),
+        (Some((_, id, name)), false) => format!("Target: {name} (planet {})", id.0),
+        (None, false) => "No planet left, spawn one with P".to_string(),
+    };
+    if label.0 != text {
+        label.0 = text;
+    }
+}
+
+fn leave_sandbox(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Settings);
     }
-    // No player entity found → end game
-    next_state.set(GameState::Settings);
-    // Update UI text instead of printing
 }
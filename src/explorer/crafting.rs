@@ -0,0 +1,323 @@
+use bevy::prelude::*;
+use common_game::components::resource::*;
+use common_game::protocols::planet_explorer::ExplorerToPlanet;
+use std::fmt;
+
+use crate::GameState;
+use crate::LandedPlanetDialog;
+use crate::explorer::dialog::create_button;
+use crate::explorer::handler::{ExplorerError, ExplorerHandler};
+use crate::explorer::inventory::{COMPLEX_RESOURCE_TYPES, Inventory};
+use crate::explorer::{Docking, Landed, Selected};
+use crate::log::LogEvent;
+use crate::theme;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ingredient {
+    Basic(BasicResourceType),
+    Complex(ComplexResourceType),
+}
+
+impl fmt::Display for Ingredient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ingredient::Basic(resource_type) => write!(f, "{resource_type:?}"),
+            Ingredient::Complex(resource_type) => write!(f, "{resource_type:?}"),
+        }
+    }
+}
+
+pub fn recipe(target: ComplexResourceType) -> [Ingredient; 2] {
+    use BasicResourceType as B;
+    use ComplexResourceType as C;
+    use Ingredient::{Basic, Complex};
+    match target {
+        C::Water => [Basic(B::Hydrogen), Basic(B::Oxygen)],
+        C::Diamond => [Basic(B::Carbon), Basic(B::Carbon)],
+        C::Life => [Complex(C::Water), Basic(B::Carbon)],
+        C::Robot => [Basic(B::Silicon), Complex(C::Life)],
+        C::Glucose => [Basic(B::Carbon), Basic(B::Hydrogen)],
+        C::AIPartner => [Complex(C::Robot), Complex(C::Diamond)],
+    }
+}
+
+fn count(inventory: &Inventory, ingredient: Ingredient) -> usize {
+    match ingredient {
+        Ingredient::Basic(resource_type) => inventory.basic_count(resource_type),
+        Ingredient::Complex(resource_type) => inventory.complex_count(resource_type),
+    }
+}
+
+pub fn can_craft(inventory: &Inventory, target: ComplexResourceType) -> bool {
+    let [first, second] = recipe(target);
+    if first == second {
+        count(inventory, first) >= 2
+    } else {
+        count(inventory, first) >= 1 && count(inventory, second) >= 1
+    }
+}
+
+// Moves the ingredients out of the inventory, they come back if the planet refuses
+pub fn take_request(
+    inventory: &mut Inventory,
+    target: ComplexResourceType,
+) -> Option<ComplexResourceRequest> {
+    use BasicResource as B;
+    use BasicResourceType as BT;
+    use ComplexResource as C;
+    use ComplexResourceType as CT;
+
+    if !can_craft(inventory, target) {
+        return None;
+    }
+    let request = match target {
+        CT::Water => match (
+            inventory.take_basic(BT::Hydrogen),
+            inventory.take_basic(BT::Oxygen),
+        ) {
+            (Some(B::Hydrogen(h)), Some(B::Oxygen(o))) => ComplexResourceRequest::Water(h, o),
+            _ => return None,
+        },
+        CT::Diamond => match (
+            inventory.take_basic(BT::Carbon),
+            inventory.take_basic(BT::Carbon),
+        ) {
+            (Some(B::Carbon(c1)), Some(B::Carbon(c2))) => ComplexResourceRequest::Diamond(c1, c2),
+            _ => return None,
+        },
+        CT::Life => match (
+            inventory.take_complex(CT::Water),
+            inventory.take_basic(BT::Carbon),
+        ) {
+            (Some(C::Water(w)), Some(B::Carbon(c))) => ComplexResourceRequest::Life(w, c),
+            _ => return None,
+        },
+        CT::Robot => match (
+            inventory.take_basic(BT::Silicon),
+            inventory.take_complex(CT::Life),
+        ) {
+            (Some(B::Silicon(s)), Some(C::Life(l))) => ComplexResourceRequest::Robot(s, l),
+            _ => return None,
+        },
+        CT::Glucose => match (
+            inventory.take_basic(BT::Carbon),
+            inventory.take_basic(BT::Hydrogen),
+        ) {
+            (Some(B::Carbon(c)), Some(B::Hydrogen(h))) => ComplexResourceRequest::Glucose(c, h),
+            _ => return None,
+        },
+        CT::AIPartner => match (
+            inventory.take_complex(CT::Robot),
+            inventory.take_complex(CT::Diamond),
+        ) {
+            (Some(C::Robot(r)), Some(C::Diamond(d))) => ComplexResourceRequest::AIPartner(r, d),
+            _ => return None,
+        },
+    };
+    Some(request)
+}
+
+// Puts the ingredients of a request that never reached the planet back into the inventory
+pub fn give_back(inventory: &mut Inventory, request: ComplexResourceRequest) {
+    use BasicResource as B;
+    use ComplexResource as C;
+
+    match request {
+        ComplexResourceRequest::Water(h, o) => {
+            inventory.add_basic(B::Hydrogen(h));
+            inventory.add_basic(B::Oxygen(o));
+        }
+        ComplexResourceRequest::Diamond(c1, c2) => {
+            inventory.add_basic(B::Carbon(c1));
+            inventory.add_basic(B::Carbon(c2));
+        }
+        ComplexResourceRequest::Life(w, c) => {
+            inventory.add_complex(C::Water(w));
+            inventory.add_basic(B::Carbon(c));
+        }
+        ComplexResourceRequest::Robot(s, l) => {
+            inventory.add_basic(B::Silicon(s));
+            inventory.add_complex(C::Life(l));
+        }
+        ComplexResourceRequest::Glucose(c, h) => {
+            inventory.add_basic(B::Carbon(c));
+            inventory.add_basic(B::Hydrogen(h));
+        }
+        ComplexResourceRequest::AIPartner(r, d) => {
+            inventory.add_complex(C::Robot(r));
+            inventory.add_complex(C::Diamond(d));
+        }
+    }
+}
+
+// Sends a request built by take_request, the ingredients are kept if the planet is unreachable
+pub fn send_craft_request(
+    handler: &ExplorerHandler,
+    inventory: &mut Inventory,
+    request: ComplexResourceRequest,
+) -> Result<u32, ExplorerError> {
+    handler
+        .try_send_to_planet(ExplorerToPlanet::CombineResourceRequest {
+            explorer_id: handler.id(),
+            msg: request,
+        })
+        .map_err(|(e, msg)| {
+            if let ExplorerToPlanet::CombineResourceRequest { msg, .. } = msg {
+                give_back(inventory, msg);
+            }
+            e
+        })
+}
+
+// The answer to a CombineResourceRequest, `Err` holds the reason given by the planet
+#[derive(Message)]
+pub struct CraftOutcome {
+    pub planet_id: u32,
+    pub result: Result<ComplexResourceType, String>,
+}
+
+#[derive(Component)]
+pub struct CraftButton(pub ComplexResourceType);
+
+#[derive(Component)]
+pub struct CraftFeedback;
+
+pub fn crafting_panel(asset_server: &Res<AssetServer>, state: GameState) -> impl Bundle {
+    let padding = 12.0;
+
+    let buttons = COMPLEX_RESOURCE_TYPES.map(|target| {
+        let [first, second] = recipe(target);
+        create_button(
+            asset_server,
+            format!("{target:?} = {first} + {second}"),
+            CraftButton(target),
+            percent(100.0),
+            px(36.0),
+            px(0.0),
+            px(2.0),
+            theme::color::MUTED,
+        )
+    });
+
+    (
+        DespawnOnExit(state),
+        Node {
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            row_gap: px(4.0),
+            left: percent(2.0),
+            bottom: percent(5.0),
+            width: percent(20.0),
+            padding: UiRect::all(px(padding)),
+            ..default()
+        },
+        // Shown and hidden together with the landed dialog
+        LandedPlanetDialog,
+        Visibility::Hidden,
+        theme::background_color(),
+        Children::spawn((
+            Spawn((
+                Text::new("Crafting"),
+                theme::title_font(asset_server),
+                theme::text_color(),
+            )),
+            SpawnIter(buttons.into_iter()),
+            Spawn((
+                Text::new(""),
+                theme::basic_font(asset_server),
+                theme::text_color(),
+                CraftFeedback,
+            )),
+        )),
+    )
+}
+
+// Only the recipes of the landed planet are listed, the craftable ones are highlighted
+pub fn update_crafting_panel(
+    explorer: Single<(&ExplorerHandler, &Inventory), (With<Selected>, With<Landed>)>,
+    mut button_query: Query<(&CraftButton, &mut Node, &mut BorderColor, &Children)>,
+    mut text_query: Query<&mut TextColor>,
+) {
+    let (handler, inventory) = *explorer;
+    for (button, mut node, mut border, children) in button_query.iter_mut() {
+        let display = if handler.combinations.contains(&button.0) {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if node.display != display {
+            node.display = display;
+        }
+        let color = if can_craft(inventory, button.0) {
+            theme::color::TEXT
+        } else {
+            theme::color::MUTED
+        };
+        *border = BorderColor::all(color);
+        for child in children.iter() {
+            if let Ok(mut text_color) = text_query.get_mut(child) {
+                text_color.0 = color;
+            }
+        }
+    }
+}
+
+pub fn craft_button_system(
+    interaction_query: Query<(&Interaction, &CraftButton), Changed<Interaction>>,
+    explorer: Single<
+        (&ExplorerHandler, &mut Inventory),
+        (With<Selected>, With<Landed>, Without<Docking>),
+    >,
+    mut feedback: Single<(&mut Text, &mut TextColor), With<CraftFeedback>>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    let (handler, mut inventory) = explorer.into_inner();
+    let (text, color) = &mut *feedback;
+    for (interaction, button) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let target = button.0;
+        if !handler.combinations.contains(&target) {
+            text.0 = format!("This planet cannot craft {target:?}");
+            color.0 = theme::color::ERROR;
+            continue;
+        }
+        let Some(request) = take_request(&mut inventory, target) else {
+            let [first, second] = recipe(target);
+            text.0 = format!("{target:?} needs {first} and {second}");
+            color.0 = theme::color::ERROR;
+            continue;
+        };
+        match send_craft_request(handler, &mut inventory, request) {
+            Ok(_) => {
+                text.0 = format!("Crafting {target:?}...");
+                color.0 = theme::color::MUTED;
+            }
+            Err(e) => {
+                text.0 = e.to_string();
+                color.0 = theme::color::ERROR;
+                log.write(LogEvent::error(e.to_string()));
+            }
+        }
+    }
+}
+
+pub fn update_craft_feedback(
+    mut outcomes: MessageReader<CraftOutcome>,
+    mut feedback: Single<(&mut Text, &mut TextColor), With<CraftFeedback>>,
+) {
+    let (text, color) = &mut *feedback;
+    for outcome in outcomes.read() {
+        match &outcome.result {
+            Ok(resource_type) => {
+                text.0 = format!("Crafted {resource_type:?}");
+                color.0 = theme::color::DEFLECTED;
+            }
+            Err(reason) => {
+                text.0 = format!("The planet refused: {reason}");
+                color.0 = theme::color::ERROR;
+            }
+        }
+    }
+}
@@ -0,0 +1,434 @@
+use bevy::prelude::*;
+use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
+use common_game::protocols::planet_explorer::ExplorerToPlanet;
+
+use crate::AvailableEnergyCellButton;
+use crate::ExtractResourceButton;
+use crate::GameState;
+use crate::LandedPlanetDialog;
+use crate::NoButton;
+use crate::PlanetDialog;
+use crate::ScanButton;
+use crate::SupportedResourceButton;
+use crate::TakeOffPlanetButton;
+use crate::YesButton;
+use crate::explorer::handler::ExplorerHandler;
+use crate::explorer::movement::{ReachedPlanet, SkippedPlanet};
+use crate::explorer::strategy::Autopilot;
+use crate::explorer::{Docking, Landed, Roaming, Selected, request_landing, request_take_off};
+use crate::log::{LogEvent, LogKind};
+use crate::orchestrator::Orchestrator;
+use crate::planet::{PlanetCell, PlanetId, PlanetRocket, PlanetUi, cell_string};
+use crate::theme;
+
+#[derive(Component)]
+pub struct PlanetDialogTitle;
+
+#[derive(Component)]
+pub struct PlanetDialogStatus;
+
+pub fn land_on_planet_dialog(asset_server: &Res<AssetServer>, state: GameState) -> impl Bundle {
+    (
+        DespawnOnExit(state),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: percent(30.0),
+            left: percent(30.0),
+            width: percent(40.0),
+            height: percent(30.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        theme::background_color(),
+        PlanetDialog,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(px(20.0)),
+                width: percent(100.0),
+                justify_content: JustifyContent::SpaceBetween,
+                ..default()
+            },
+            children![
+                (
+                    Text::new(""),
+                    theme::title_font(asset_server),
+                    theme::text_color(),
+                    PlanetDialogTitle,
+                ),
+                (
+                    Text::new(""),
+                    theme::basic_font(asset_server),
+                    theme::text_color(),
+                    PlanetDialogStatus,
+                ),
+                (
+                    Node {
+                        flex_direction: FlexDirection::Row,
+                        justify_content: JustifyContent::SpaceBetween,
+                        ..default()
+                    },
+                    children![
+                        create_button(
+                            asset_server,
+                            "Land",
+                            YesButton,
+                            percent(30.0),
+                            px(50.0),
+                            Val::Auto,
+                            px(5.0),
+                            Color::WHITE
+                        ),
+                        create_button(
+                            asset_server,
+                            "Scan",
+                            ScanButton,
+                            percent(30.0),
+                            px(50.0),
+                            Val::Auto,
+                            px(5.0),
+                            Color::WHITE
+                        ),
+                        create_button(
+                            asset_server,
+                            "Skip",
+                            NoButton,
+                            percent(30.0),
+                            px(50.0),
+                            Val::Auto,
+                            px(5.0),
+                            Color::WHITE
+                        ),
+                    ],
+                )
+            ],
+        )],
+    )
+}
+
+pub fn landed_planet_dialog(asset_server: &Res<AssetServer>, state: GameState) -> impl Bundle {
+    (
+        DespawnOnExit(state),
+        Node {
+            flex_direction: FlexDirection::Column,
+            position_type: PositionType::Absolute,
+            justify_content: JustifyContent::SpaceBetween,
+            bottom: percent(5.0),
+            left: percent(35.0),
+            width: percent(30.0),
+            min_width: px(400.0),
+            height: percent(40.0),
+            padding: UiRect::all(px(12.0)),
+            ..default()
+        },
+        LandedPlanetDialog,
+        Visibility::Hidden,
+        theme::background_color(),
+        children![
+            (
+                Text::new("What would you do on this planet?"),
+                theme::title_font(asset_server),
+                theme::text_color(),
+            ),
+            create_button(
+                asset_server,
+                "Supported Resource",
+                SupportedResourceButton,
+                percent(90.0),
+                percent(15.0),
+                percent(5.0),
+                px(5.0),
+                Color::WHITE
+            ),
+            create_button(
+                asset_server,
+                "Extract Resource",
+                ExtractResourceButton,
+                percent(90.0),
+                percent(15.0),
+                percent(5.0),
+                px(5.0),
+                Color::WHITE
+            ),
+            create_button(
+                asset_server,
+                "Available Energy Cell",
+                AvailableEnergyCellButton,
+                percent(90.0),
+                percent(15.0),
+                percent(5.0),
+                px(5.0),
+                Color::WHITE
+            ),
+            create_button(
+                asset_server,
+                "Take off",
+                TakeOffPlanetButton,
+                percent(90.0),
+                percent(15.0),
+                percent(5.0),
+                px(5.0),
+                Color::WHITE
+            ),
+        ],
+    )
+}
+
+pub fn create_button(
+    asset_server: &Res<AssetServer>,
+    text: impl Into<String>,
+    button_component: impl Component,
+    width: Val,
+    height: Val,
+    left: Val,
+    border_stroke: Val,
+    border_color: Color,
+) -> impl Bundle {
+    (
+        Button,
+        button_component,
+        Node {
+            width,
+            height,
+            left,
+            border: UiRect::all(border_stroke),
+            // horizontally center child text
+            justify_content: JustifyContent::Center,
+            // vertically center child text
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BorderColor::all(border_color),
+        children![(
+            Text::new(text),
+            theme::title_font(asset_server),
+            theme::text_color(),
+        )],
+    )
+}
+
+pub fn landed_dialog_visibility(
+    mut dialog_query: Query<&mut Visibility, With<LandedPlanetDialog>>,
+    explorer_landed: Query<
+        (),
+        (
+            With<Selected>,
+            With<Landed>,
+            Without<Docking>,
+            Without<Autopilot>,
+        ),
+    >,
+) {
+    let visibility = if explorer_landed.is_empty() {
+        Visibility::Hidden
+    } else {
+        Visibility::Visible
+    };
+    for mut dialog in &mut dialog_query {
+        dialog.set_if_neq(visibility);
+    }
+}
+
+pub fn yes_button_system(
+    mut commands: Commands,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<YesButton>)>,
+    explorer: Single<(Entity, &ExplorerHandler, &ReachedPlanet), (With<Selected>, With<Roaming>)>,
+    planet_query: Query<&PlanetId>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    let (entity, handler, reached) = *explorer;
+    for interaction in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(planet_id) = planet_query.get(reached.0) else {
+            continue;
+        };
+        if let Err(e) = request_landing(
+            &mut commands,
+            entity,
+            handler,
+            reached.0,
+            planet_id.0,
+            &mut orch,
+        ) {
+            log.write(LogEvent::error(e.to_string()));
+        }
+        info!("Land button pressed");
+    }
+}
+
+pub fn no_button_system(
+    mut commands: Commands,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<NoButton>)>,
+    explorer: Single<(Entity, &ReachedPlanet), (With<Selected>, With<Roaming>)>,
+) {
+    let (entity, reached) = *explorer;
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            // Shown again once the explorer reaches another planet
+            commands.entity(entity).insert(SkippedPlanet(reached.0));
+            info!("Skip button pressed");
+        }
+    }
+}
+
+pub fn scan_button_system(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<ScanButton>)>,
+    explorer: Single<&ReachedPlanet, (With<Selected>, With<Roaming>)>,
+    planet_query: Query<(&Name, &PlanetId)>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for interaction in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok((name, planet_id)) = planet_query.get(explorer.0) else {
+            continue;
+        };
+        // The answer refreshes the planet panel, and the dialog with it
+        match orch.send_to_planet_id(planet_id.0, OrchestratorToPlanet::InternalStateRequest) {
+            Ok(_) => {
+                log.write(LogEvent::planet(
+                    LogKind::Info,
+                    planet_id.0,
+                    format!("Scanning {name}"),
+                ));
+            }
+            Err(e) => {
+                log.write(LogEvent::error(e.to_string()));
+            }
+        }
+    }
+}
+
+// Names the planet in range of the selected explorer and shows its last known state
+pub fn update_planet_dialog(
+    explorer: Option<
+        Single<(&ReachedPlanet, Option<&SkippedPlanet>), (With<Selected>, With<Roaming>)>,
+    >,
+    planet_query: Query<(&Name, &PlanetId)>,
+    ui_query: Query<(&PlanetUi, &Children)>,
+    cell_query: Query<&PlanetCell>,
+    rocket_query: Query<&PlanetRocket>,
+    mut dialog: Single<&mut Visibility, With<PlanetDialog>>,
+    mut title: Single<&mut Text, With<PlanetDialogTitle>>,
+    mut status: Single<&mut Text, (With<PlanetDialogStatus>, Without<PlanetDialogTitle>)>,
+) {
+    let in_range = explorer.and_then(|explorer| {
+        let (reached, skipped) = *explorer;
+        let skipped = skipped.is_some_and(|skipped| skipped.0 == reached.0);
+        (!skipped).then_some(reached.0)
+    });
+    let Some((planet, (name, planet_id))) =
+        in_range.and_then(|planet| planet_query.get(planet).ok().map(|found| (planet, found)))
+    else {
+        dialog.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    dialog.set_if_neq(Visibility::Visible);
+
+    let children = ui_query
+        .iter()
+        .find(|(ui, _)| ui.0 == planet)
+        .map(|(_, children)| children);
+    let cell = children.and_then(|children| children.iter().find_map(|c| cell_query.get(c).ok()));
+    let rocket =
+        children.and_then(|children| children.iter().find_map(|c| rocket_query.get(c).ok()));
+
+    let new_title = format!("{name} (planet {}) is in range", planet_id.0);
+    if title.0 != new_title {
+        title.0 = new_title;
+    }
+    let new_status = match (cell, rocket) {
+        (Some(cell), Some(rocket)) => format!(
+            "Energy cells: {} ({}/{} charged)\nRocket: {}",
+            cell_string(cell),
+            cell.charged_cell,
+            cell.num_cell,
+            if rocket.0 { "ready 󱎯" } else { "none" }
+        ),
+        _ => "State unknown, press Scan".to_string(),
+    };
+    if status.0 != new_status {
+        status.0 = new_status;
+    }
+}
+
+pub fn take_off_button_system(
+    mut commands: Commands,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<TakeOffPlanetButton>)>,
+    explorer: Single<(Entity, &ExplorerHandler, &Landed), (With<Selected>, Without<Docking>)>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    let (entity, handler, landed) = *explorer;
+    for interaction in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let Err(e) = request_take_off(&mut commands, entity, handler, landed, &mut orch) {
+            log.write(LogEvent::error(e.to_string()));
+        }
+        info!("Take off button pressed");
+    }
+}
+
+pub fn supported_resource_button_system(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<SupportedResourceButton>)>,
+    explorer: Single<&ExplorerHandler, (With<Selected>, With<Landed>, Without<Docking>)>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Pressed
+            && let Err(e) = explorer.send_to_planet(ExplorerToPlanet::SupportedResourceRequest {
+                explorer_id: explorer.id(),
+            })
+        {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+}
+
+pub fn available_energy_cell_button_system(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<AvailableEnergyCellButton>)>,
+    explorer: Single<&ExplorerHandler, (With<Selected>, With<Landed>, Without<Docking>)>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for interaction in &interaction_query {
+        if *interaction == Interaction::Pressed
+            && let Err(e) = explorer.send_to_planet(ExplorerToPlanet::AvailableEnergyCellRequest {
+                explorer_id: explorer.id(),
+            })
+        {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+}
+
+pub fn generate_supported_resource_button_system(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<ExtractResourceButton>)>,
+    explorer: Single<&ExplorerHandler, (With<Selected>, With<Landed>, Without<Docking>)>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for interaction in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        // Filled in by the SupportedResourceResponse sent right after landing
+        let Some(&resource) = explorer.supported.first() else {
+            log.write(LogEvent::info(
+                "This planet has not told which resources it supports yet",
+            ));
+            continue;
+        };
+        if let Err(e) = explorer.send_to_planet(ExplorerToPlanet::GenerateResourceRequest {
+            explorer_id: explorer.id(),
+            resource,
+        }) {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+}
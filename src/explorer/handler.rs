@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+use common_game::components::resource::{BasicResourceType, ComplexResourceType};
+use common_game::protocols::planet_explorer::*;
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ExplorerError {
+    NotLanded(u32),
+    Disconnected { explorer_id: u32, planet_id: u32 },
+}
+
+impl fmt::Display for ExplorerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExplorerError::NotLanded(id) => write!(f, "explorer {id} is not landed on a planet"),
+            ExplorerError::Disconnected {
+                explorer_id,
+                planet_id,
+            } => write!(
+                f,
+                "the channel between explorer {explorer_id} and planet {planet_id} is disconnected"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExplorerError {}
+
+// The explorer side of the planet-explorer protocol, it can only talk to the planet it landed on
+#[derive(Component)]
+pub struct ExplorerHandler {
+    id: u32,
+    planet_tx: Sender<PlanetToExplorer>,
+    planet_rx: Receiver<PlanetToExplorer>,
+    landed: Option<(u32, Sender<ExplorerToPlanet>)>,
+    // Still set after take off, late answers carrying resources come from this planet
+    last_planet: Option<u32>,
+    pub supported: Vec<BasicResourceType>,
+    pub combinations: Vec<ComplexResourceType>,
+}
+
+impl ExplorerHandler {
+    pub fn new(id: u32) -> Self {
+        let (planet_tx, planet_rx) = unbounded();
+        Self {
+            id,
+            planet_tx,
+            planet_rx,
+            landed: None,
+            last_planet: None,
+            supported: Vec::new(),
+            combinations: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn planet_tx(&self) -> Sender<PlanetToExplorer> {
+        self.planet_tx.clone()
+    }
+
+    pub fn landed_on(&self) -> Option<u32> {
+        self.landed.as_ref().map(|(planet_id, _)| *planet_id)
+    }
+
+    pub fn last_planet(&self) -> Option<u32> {
+        self.last_planet
+    }
+
+    pub fn land(&mut self, planet_id: u32, expl_tx: Sender<ExplorerToPlanet>) {
+        self.landed = Some((planet_id, expl_tx));
+        self.last_planet = Some(planet_id);
+        self.supported.clear();
+        self.combinations.clear();
+    }
+
+    pub fn take_off(&mut self) {
+        self.landed = None;
+        self.supported.clear();
+        self.combinations.clear();
+    }
+
+    pub fn send_to_planet(&self, msg: ExplorerToPlanet) -> Result<u32, ExplorerError> {
+        self.try_send_to_planet(msg).map_err(|(e, _)| e)
+    }
+
+    // Like send_to_planet, but hands the message back when it could not be delivered
+    pub fn try_send_to_planet(
+        &self,
+        msg: ExplorerToPlanet,
+    ) -> Result<u32, (ExplorerError, ExplorerToPlanet)> {
+        let Some((planet_id, tx)) = self.landed.as_ref() else {
+            return Err((ExplorerError::NotLanded(self.id), msg));
+        };
+        info!(
+            "explorer {} sending {:?} to planet {planet_id}",
+            self.id, &msg
+        );
+        tx.send(msg).map_err(|e| {
+            (
+                ExplorerError::Disconnected {
+                    explorer_id: self.id,
+                    planet_id: *planet_id,
+                },
+                e.0,
+            )
+        })?;
+        Ok(*planet_id)
+    }
+
+    pub fn drain(&self) -> Vec<PlanetToExplorer> {
+        self.planet_rx.try_iter().collect()
+    }
+}
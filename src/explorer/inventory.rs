@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+use common_game::components::resource::*;
+use std::collections::HashMap;
+
+use crate::GameState;
+use crate::explorer::Selected;
+use crate::theme;
+
+pub const BASIC_RESOURCE_TYPES: [BasicResourceType; 4] = [
+    BasicResourceType::Oxygen,
+    BasicResourceType::Hydrogen,
+    BasicResourceType::Carbon,
+    BasicResourceType::Silicon,
+];
+
+pub const COMPLEX_RESOURCE_TYPES: [ComplexResourceType; 6] = [
+    ComplexResourceType::Water,
+    ComplexResourceType::Diamond,
+    ComplexResourceType::Life,
+    ComplexResourceType::Robot,
+    ComplexResourceType::Glucose,
+    ComplexResourceType::AIPartner,
+];
+
+pub fn basic_type(resource: &BasicResource) -> BasicResourceType {
+    match resource {
+        BasicResource::Oxygen(_) => BasicResourceType::Oxygen,
+        BasicResource::Hydrogen(_) => BasicResourceType::Hydrogen,
+        BasicResource::Carbon(_) => BasicResourceType::Carbon,
+        BasicResource::Silicon(_) => BasicResourceType::Silicon,
+    }
+}
+
+pub fn complex_type(resource: &ComplexResource) -> ComplexResourceType {
+    match resource {
+        ComplexResource::Water(_) => ComplexResourceType::Water,
+        ComplexResource::Diamond(_) => ComplexResourceType::Diamond,
+        ComplexResource::Life(_) => ComplexResourceType::Life,
+        ComplexResource::Robot(_) => ComplexResourceType::Robot,
+        ComplexResource::Glucose(_) => ComplexResourceType::Glucose,
+        ComplexResource::AIPartner(_) => ComplexResourceType::AIPartner,
+    }
+}
+
+// Carried by the explorer, it is not touched by landing or taking off
+#[derive(Component, Default)]
+pub struct Inventory {
+    basic: HashMap<BasicResourceType, Vec<BasicResource>>,
+    complex: HashMap<ComplexResourceType, Vec<ComplexResource>>,
+}
+
+impl Inventory {
+    pub fn add_basic(&mut self, resource: BasicResource) {
+        self.basic
+            .entry(basic_type(&resource))
+            .or_default()
+            .push(resource);
+    }
+
+    pub fn take_basic(&mut self, resource_type: BasicResourceType) -> Option<BasicResource> {
+        self.basic.get_mut(&resource_type)?.pop()
+    }
+
+    pub fn basic_count(&self, resource_type: BasicResourceType) -> usize {
+        self.basic.get(&resource_type).map_or(0, Vec::len)
+    }
+
+    pub fn add_complex(&mut self, resource: ComplexResource) {
+        self.complex
+            .entry(complex_type(&resource))
+            .or_default()
+            .push(resource);
+    }
+
+    pub fn take_complex(&mut self, resource_type: ComplexResourceType) -> Option<ComplexResource> {
+        self.complex.get_mut(&resource_type)?.pop()
+    }
+
+    pub fn complex_count(&self, resource_type: ComplexResourceType) -> usize {
+        self.complex.get(&resource_type).map_or(0, Vec::len)
+    }
+
+    // Gives back what a planet returned from a failed combination
+    pub fn add_generic(&mut self, resource: GenericResource) {
+        match resource {
+            GenericResource::BasicResources(resource) => self.add_basic(resource),
+            GenericResource::ComplexResources(resource) => self.add_complex(resource),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct InventoryCount(pub BasicResourceType);
+
+#[derive(Component)]
+pub struct ComplexInventoryCount(pub ComplexResourceType);
+
+pub fn inventory_panel(asset_server: &Res<AssetServer>, state: GameState) -> impl Bundle {
+    let padding = 12.0;
+
+    let panel = (
+        DespawnOnExit(state),
+        Node {
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            bottom: percent(5.0),
+            right: percent(2.0),
+            width: percent(25.0),
+            padding: UiRect::all(px(padding)),
+            ..default()
+        },
+        theme::background_color(),
+    );
+    let title = (
+        Text::new("Inventory"),
+        theme::title_font(asset_server),
+        theme::text_color(),
+    );
+    let basic = BASIC_RESOURCE_TYPES.map(|resource_type| {
+        (
+            Text::new(format!("{resource_type:?}: 0")),
+            theme::basic_font(asset_server),
+            theme::text_color(),
+            InventoryCount(resource_type),
+        )
+    });
+    let complex = COMPLEX_RESOURCE_TYPES.map(|resource_type| {
+        (
+            Text::new(format!("{resource_type:?}: 0")),
+            theme::basic_font(asset_server),
+            theme::text_color(),
+            ComplexInventoryCount(resource_type),
+        )
+    });
+
+    (
+        panel,
+        Children::spawn((
+            Spawn(title),
+            SpawnIter(basic.into_iter()),
+            SpawnIter(complex.into_iter()),
+        )),
+    )
+}
+
+pub fn update_inventory_panel(
+    inventory: Single<&Inventory, (With<Selected>, Or<(Changed<Inventory>, Added<Selected>)>)>,
+    mut count_query: Query<(&mut Text, &InventoryCount)>,
+    mut complex_query: Query<(&mut Text, &ComplexInventoryCount), Without<InventoryCount>>,
+) {
+    for (mut text, count) in count_query.iter_mut() {
+        text.0 = format!("{:?}: {}", count.0, inventory.basic_count(count.0));
+    }
+    for (mut text, count) in complex_query.iter_mut() {
+        text.0 = format!("{:?}: {}", count.0, inventory.complex_count(count.0));
+    }
+}
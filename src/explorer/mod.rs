@@ -1,27 +1,444 @@
-use bevy::prelude::Component;
-use bevy::prelude::Entity;
+use bevy::prelude::*;
+use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
+use common_game::protocols::planet_explorer::*;
 
+use crate::GameState;
+use crate::log::{LogEvent, LogKind};
+use crate::orchestrator::lifecycle::{ExplorerTransit, PlanetDestroyed, Transit};
+use crate::orchestrator::pending::{ReplyKind, RequestTimedOut};
+use crate::orchestrator::{Orchestrator, OrchestratorError};
+use crate::planet::inspector::PlanetReport;
+use crate::planet::*;
+
+pub mod crafting;
+pub mod dialog;
+pub mod handler;
+pub mod inventory;
 pub mod movement;
+pub mod strategy;
+
+use crafting::CraftOutcome;
+use handler::ExplorerHandler;
+use inventory::*;
+use movement::{ReachedPlanet, SkippedPlanet, Velocity};
+use strategy::{Autopilot, ExplorerRoster};
 
 #[derive(Component)]
 pub struct Explorer {
-    _target_planet: Option<Entity>,
-    _travel_speed: f32,
+    pub target_planet: Option<Entity>,
+    pub travel_speed: f32,
 }
 
 #[derive(Component)]
 pub struct Roaming;
 
+// The explorer driven by the keyboard and the dialogs, Tab switches to the next one
+#[derive(Component)]
+pub struct Selected;
+
 #[derive(Component)]
 pub struct Landed {
     pub(crate) planet: Entity,
 }
 
+// Waiting for a planet to answer a landing or take off request
+#[derive(Component)]
+pub struct Docking {
+    pub planet: Entity,
+    pub planet_id: u32,
+}
+
 impl Explorer {
-    pub fn new(_target_planet: Option<Entity>, _travel_speed: f32) -> Self {
+    pub fn new(target_planet: Option<Entity>, travel_speed: f32) -> Self {
         Self {
-            _target_planet,
-            _travel_speed,
+            target_planet,
+            travel_speed,
+        }
+    }
+}
+
+// Shared by the landing dialog and the autopilot
+pub fn request_landing(
+    commands: &mut Commands,
+    explorer: Entity,
+    handler: &ExplorerHandler,
+    planet: Entity,
+    planet_id: u32,
+    orch: &mut Orchestrator,
+) -> Result<(), OrchestratorError> {
+    orch.send_to_planet_id(
+        planet_id,
+        OrchestratorToPlanet::IncomingExplorerRequest {
+            explorer_id: handler.id(),
+            new_mpsc_sender: handler.planet_tx(),
+        },
+    )?;
+    commands
+        .entity(explorer)
+        .remove::<(Roaming, ReachedPlanet, SkippedPlanet)>()
+        .insert((Docking { planet, planet_id }, Velocity::default()));
+    Ok(())
+}
+
+pub fn request_take_off(
+    commands: &mut Commands,
+    explorer: Entity,
+    handler: &ExplorerHandler,
+    landed: &Landed,
+    orch: &mut Orchestrator,
+) -> Result<(), OrchestratorError> {
+    let Some(planet_id) = handler.landed_on() else {
+        return Ok(());
+    };
+    orch.send_to_planet_id(
+        planet_id,
+        OrchestratorToPlanet::OutgoingExplorerRequest {
+            explorer_id: handler.id(),
+        },
+    )?;
+    commands.entity(explorer).insert(Docking {
+        planet: landed.planet,
+        planet_id,
+    });
+    Ok(())
+}
+
+pub fn explorer(
+    asset_server: &Res<AssetServer>,
+    id: u32,
+    position: Vec2,
+    state: GameState,
+) -> impl Bundle {
+    (
+        DespawnOnExit(state),
+        Sprite {
+            image: asset_server.load("sprites/explorer.png"),
+            custom_size: Some(Vec2::new(50.0, 50.0)),
+            ..default()
+        },
+        Transform::from_xyz(position.x, position.y, 1.0),
+        Name::new(format!("Explorer {id}")),
+        Explorer::new(None, 150.0),
+        Velocity::default(),
+        ExplorerHandler::new(id),
+        Inventory::default(),
+        Roaming,
+    )
+}
+
+pub fn explorer_plugin(app: &mut App) {
+    app.add_message::<CraftOutcome>()
+        .init_resource::<ExplorerRoster>()
+        .add_systems(
+            Update,
+            (
+                (
+                    select_next_explorer,
+                    movement::explorer_movement_system_wasd,
+                    movement::click_to_travel_system,
+                    movement::travel_to_target_system,
+                    movement::integrate_explorer_motion,
+                    movement::check_explorer_reach,
+                )
+                    .chain(),
+                (
+                    dialog::yes_button_system,
+                    dialog::no_button_system,
+                    dialog::scan_button_system,
+                    dialog::take_off_button_system,
+                    dialog::supported_resource_button_system,
+                    dialog::available_energy_cell_button_system,
+                    dialog::generate_supported_resource_button_system,
+                    crafting::craft_button_system,
+                    strategy::autopilot_system,
+                )
+                    .chain(),
+                handle_explorer_transits,
+                eject_from_destroyed_planets,
+                listen_to_explorer_channels,
+                dialog::landed_dialog_visibility,
+                dialog::update_planet_dialog,
+                inventory::update_inventory_panel,
+                crafting::update_crafting_panel,
+                crafting::update_craft_feedback,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+}
+
+fn select_next_explorer(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    explorer_query: Query<(Entity, &ExplorerHandler, Has<Selected>), Without<Autopilot>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Tab) {
+        return;
+    }
+    let mut explorers: Vec<_> = explorer_query.iter().collect();
+    if explorers.len() < 2 {
+        return;
+    }
+    explorers.sort_by_key(|(_, handler, _)| handler.id());
+    let current = explorers
+        .iter()
+        .position(|&(_, _, selected)| selected)
+        .unwrap_or(explorers.len() - 1);
+    let (next, handler, _) = explorers[(current + 1) % explorers.len()];
+
+    for &(entity, _, selected) in &explorers {
+        if selected {
+            commands
+                .entity(entity)
+                .remove::<(Selected, ReachedPlanet, SkippedPlanet)>();
+        }
+    }
+    commands.entity(next).insert(Selected);
+    info!("Explorer {} selected", handler.id());
+}
+
+fn handle_explorer_transits(
+    mut commands: Commands,
+    mut transits: MessageReader<ExplorerTransit>,
+    mut timed_out: MessageReader<RequestTimedOut>,
+    mut explorer_query: Query<(Entity, &mut ExplorerHandler, &Docking, &mut Transform)>,
+    planet_query: Query<(&Name, &Transform), (With<Planet>, Without<ExplorerHandler>)>,
+    orch: Res<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for transit in transits.read() {
+        let Some((entity, mut handler, docking, mut transform)) = explorer_query
+            .iter_mut()
+            .find(|(_, handler, _, _)| handler.id() == transit.explorer_id)
+        else {
+            warn!("no docking explorer with id {}", transit.explorer_id);
+            continue;
+        };
+        let Ok((name, planet_transform)) = planet_query.get(docking.planet) else {
+            continue;
+        };
+        let planet_id = transit.planet_id;
+        let mut explorer = commands.entity(entity);
+        explorer.remove::<Docking>();
+
+        match (transit.direction, &transit.res) {
+            (Transit::Incoming, Ok(())) => {
+                let Some(expl_tx) = orch.explorer_sender(planet_id) else {
+                    explorer.insert(Roaming);
+                    continue;
+                };
+                handler.land(planet_id, expl_tx);
+                explorer.insert(Landed {
+                    planet: docking.planet,
+                });
+                transform.translation.x = planet_transform.translation.x;
+                transform.translation.y = planet_transform.translation.y;
+                log.write(LogEvent::planet(
+                    LogKind::Info,
+                    planet_id,
+                    format!("Explorer {} landed on {name}", handler.id()),
+                ));
+                for request in [
+                    ExplorerToPlanet::SupportedResourceRequest {
+                        explorer_id: handler.id(),
+                    },
+                    ExplorerToPlanet::SupportedCombinationRequest {
+                        explorer_id: handler.id(),
+                    },
+                ] {
+                    if let Err(e) = handler.send_to_planet(request) {
+                        log.write(LogEvent::error(e.to_string()));
+                    }
+                }
+            }
+            (Transit::Incoming, Err(e)) => {
+                explorer.insert(Roaming);
+                log.write(LogEvent::planet(
+                    LogKind::Error,
+                    planet_id,
+                    format!("refused explorer {}: {e}", handler.id()),
+                ));
+            }
+            (Transit::Outgoing, Ok(())) => {
+                handler.take_off();
+                explorer.remove::<Landed>().insert(Roaming);
+                transform.translation.y = planet_transform.translation.y - 80.0;
+                log.write(LogEvent::planet(
+                    LogKind::Info,
+                    planet_id,
+                    format!("Explorer {} took off from {name}", handler.id()),
+                ));
+            }
+            (Transit::Outgoing, Err(e)) => {
+                log.write(LogEvent::planet(
+                    LogKind::Error,
+                    planet_id,
+                    format!("did not let explorer {} take off: {e}", handler.id()),
+                ));
+            }
+        }
+    }
+
+    // Unanswered transits give control back to the player
+    for RequestTimedOut { request } in timed_out.read() {
+        let explorer_id = match request.kind {
+            ReplyKind::IncomingExplorerResponse { explorer_id }
+            | ReplyKind::OutgoingExplorerResponse { explorer_id } => explorer_id,
+            _ => continue,
+        };
+        let Some((entity, handler, _, _)) = explorer_query
+            .iter()
+            .find(|(_, handler, _, _)| handler.id() == explorer_id)
+        else {
+            continue;
+        };
+        let mut explorer = commands.entity(entity);
+        explorer.remove::<Docking>();
+        if handler.landed_on().is_none() {
+            explorer.insert(Roaming);
+        }
+    }
+}
+
+fn eject_from_destroyed_planets(
+    mut commands: Commands,
+    mut destroyed: MessageReader<PlanetDestroyed>,
+    mut explorer_query: Query<(Entity, &mut ExplorerHandler, Option<&Docking>)>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for PlanetDestroyed { planet_id } in destroyed.read() {
+        for (entity, mut handler, docking) in explorer_query.iter_mut() {
+            let docking_here = docking.is_some_and(|docking| docking.planet_id == *planet_id);
+            if handler.landed_on() != Some(*planet_id) && !docking_here {
+                continue;
+            }
+            handler.take_off();
+            commands
+                .entity(entity)
+                .remove::<(Landed, Docking, ReachedPlanet, SkippedPlanet)>()
+                .insert(Roaming);
+            log.write(LogEvent::planet(
+                LogKind::Destroyed,
+                *planet_id,
+                format!("Explorer {} escaped the planet just in time", handler.id()),
+            ));
+        }
+    }
+}
+
+fn listen_to_explorer_channels(
+    mut explorer_query: Query<(&mut ExplorerHandler, &mut Inventory)>,
+    mut report_query: Query<(&PlanetId, &mut PlanetReport)>,
+    mut orch: ResMut<Orchestrator>,
+    mut outcomes: MessageWriter<CraftOutcome>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for (mut handler, mut inventory) in explorer_query.iter_mut() {
+        let landed = handler.landed_on().is_some();
+        let Some(planet_id) = handler.landed_on().or(handler.last_planet()) else {
+            handler.drain();
+            continue;
+        };
+        for msg in handler.drain() {
+            // After take off only the answers carrying resources are still applied
+            let carries_resources = matches!(
+                msg,
+                PlanetToExplorer::GenerateResourceResponse { .. }
+                    | PlanetToExplorer::CombineResourceResponse { .. }
+            );
+            if !landed && !carries_resources {
+                continue;
+            }
+            if landed
+                && carries_resources
+                && let Err(e) =
+                    orch.send_to_planet_id(planet_id, OrchestratorToPlanet::InternalStateRequest)
+            {
+                log.write(LogEvent::error(e.to_string()));
+            }
+            let (kind, message) = match msg {
+                PlanetToExplorer::SupportedResourceResponse { resource_list } => {
+                    handler.supported = BASIC_RESOURCE_TYPES
+                        .into_iter()
+                        .filter(|resource_type| resource_list.contains(resource_type))
+                        .collect();
+                    if let Some((_, mut report)) =
+                        report_query.iter_mut().find(|(id, _)| id.0 == planet_id)
+                    {
+                        report.supported = Some(handler.supported.clone());
+                    }
+                    (
+                        LogKind::Info,
+                        format!("can generate {:?}", handler.supported),
+                    )
+                }
+                PlanetToExplorer::SupportedCombinationResponse { combination_list } => {
+                    handler.combinations = COMPLEX_RESOURCE_TYPES
+                        .into_iter()
+                        .filter(|resource_type| combination_list.contains(resource_type))
+                        .collect();
+                    if let Some((_, mut report)) =
+                        report_query.iter_mut().find(|(id, _)| id.0 == planet_id)
+                    {
+                        report.combinations = Some(handler.combinations.clone());
+                    }
+                    (
+                        LogKind::Info,
+                        format!("can combine {:?}", handler.combinations),
+                    )
+                }
+                PlanetToExplorer::GenerateResourceResponse { resource } => match resource {
+                    Some(resource) => {
+                        let message = format!("generated {resource:?}");
+                        inventory.add_basic(resource);
+                        (LogKind::Info, message)
+                    }
+                    None => (
+                        LogKind::Error,
+                        "could not generate the resource".to_string(),
+                    ),
+                },
+                PlanetToExplorer::CombineResourceResponse { complex_response } => {
+                    match complex_response {
+                        Ok(resource) => {
+                            let resource_type = complex_type(&resource);
+                            inventory.add_complex(resource);
+                            outcomes.write(CraftOutcome {
+                                planet_id,
+                                result: Ok(resource_type),
+                            });
+                            (LogKind::Info, format!("crafted {resource_type:?}"))
+                        }
+                        Err((reason, first, second)) => {
+                            inventory.add_generic(first);
+                            inventory.add_generic(second);
+                            let message = format!("refused to combine: {reason}");
+                            outcomes.write(CraftOutcome {
+                                planet_id,
+                                result: Err(reason),
+                            });
+                            (LogKind::Error, message)
+                        }
+                    }
+                }
+                PlanetToExplorer::AvailableEnergyCellResponse { available_cells } => (
+                    LogKind::Info,
+                    format!("has {available_cells} charged energy cell"),
+                ),
+                PlanetToExplorer::Stopped => (
+                    LogKind::Error,
+                    "is stopped and ignored the explorer".to_string(),
+                ),
+            };
+            info!(
+                "planet {planet_id} answered explorer {}: {message}",
+                handler.id()
+            );
+            log.write(LogEvent::planet(
+                kind,
+                planet_id,
+                format!("explorer {}: {message}", handler.id()),
+            ));
         }
     }
 }
@@ -1,124 +1,263 @@
+use bevy::prelude::ButtonInput;
+use bevy::prelude::Camera;
 use bevy::prelude::Commands;
 use bevy::prelude::Component;
 use bevy::prelude::Entity;
+use bevy::prelude::GlobalTransform;
+use bevy::prelude::Has;
+use bevy::prelude::Interaction;
+use bevy::prelude::KeyCode;
+use bevy::prelude::MouseButton;
 use bevy::prelude::Query;
 use bevy::prelude::Res;
 use bevy::prelude::Single;
 use bevy::prelude::Time;
 use bevy::prelude::Transform;
 use bevy::prelude::Vec2;
-use bevy::prelude::Visibility;
+use bevy::prelude::Window;
+use bevy::prelude::With;
+use bevy::prelude::Without;
+use bevy::prelude::info;
+use bevy::window::PrimaryWindow;
 
-//use crate::PlanetEntities;
-//use crate::Planet;
 use crate::Explorer;
+use crate::Planet;
+use crate::explorer::strategy::Autopilot;
+use crate::explorer::{Roaming, Selected};
+use crate::planet::PLANET_RADIUS;
 
-/*pub fn explorer_movement_system(
-    time: Res<Time>,
-    mut explorer_query: Query<(&mut Transform, &mut Explorer)>,
-    planet_query: Query<&Planet>,
-    planet_entities: Res<PlanetEntities>,
-) {
-    for (mut transform, mut explorer) in explorer_query.iter_mut() {
-        if let Some(target_entity) = explorer.target_planet {
-            if let Ok(target_planet) = planet_query.get(target_entity) {
-                let current_pos = Vec2::new(transform.translation.x, transform.translation.y);
-                let direction = target_planet.position() - current_pos;
-                let distance = direction.length();
-
-                if distance > 5.0 {
-                    // Still traveling
-                    let movement =
-                        direction.normalize() * explorer.travel_speed * time.delta_secs();
-                    transform.translation.x += movement.x;
-                    transform.translation.y += movement.y;
-                } else {
-                    // Arrived, switch to other planet
-                    let other_planet = planet_entities
-                        .planets
-                        .iter()
-                        .find(|&&p| p != target_entity)
-                        .copied();
-                    explorer.target_planet = other_planet;
-                    println!("Explorer arrived at {}!", target_planet.name());
-                }
-            }
-        }
-    }
-}*/
-
-use bevy::prelude::ButtonInput;
-use bevy::prelude::KeyCode;
-use bevy::prelude::With;
+pub const EXPLORER_RADIUS: f32 = 25.0;
+// Closest the explorer gets to a planet center, it bumps into the surface
+const COLLISION_DISTANCE: f32 = PLANET_RADIUS + EXPLORER_RADIUS;
+pub const REACH_DISTANCE: f32 = COLLISION_DISTANCE + 20.0;
+const ACCELERATION: f32 = 400.0;
+// Distance over which a travelling explorer slows down before arriving
+const BRAKING_DISTANCE: f32 = 60.0;
+// Extra room kept when flying around a planet that is in the way
+const AVOID_MARGIN: f32 = 15.0;
 
-use crate::Planet;
-use crate::PlanetDialog;
-use crate::explorer::Roaming;
+// The planet the roaming explorer is currently close enough to land on
+#[derive(Component)]
+pub struct ReachedPlanet(pub Entity);
 
+// The player declined to land on this planet, until the explorer leaves its range
 #[derive(Component)]
-pub(crate) struct ReachedPlanet(pub(crate) bool);
+pub struct SkippedPlanet(pub Entity);
+
+#[derive(Component, Default)]
+pub struct Velocity(pub Vec2);
+
+// Moves the velocity towards `direction * max_speed` without exceeding the acceleration
+fn accelerate(velocity: &mut Velocity, direction: Vec2, max_speed: f32, delta: f32) {
+    let change = direction * max_speed - velocity.0;
+    velocity.0 += change.clamp_length_max(ACCELERATION * delta);
+}
+
+// Heads for the side of the first planet blocking the straight line to `target`
+fn next_waypoint(position: Vec2, target: Vec2, obstacles: &[Vec2]) -> Vec2 {
+    let to_target = target - position;
+    let length = to_target.length();
+    if length <= f32::EPSILON {
+        return target;
+    }
+    let direction = to_target / length;
+    let clearance = COLLISION_DISTANCE + AVOID_MARGIN;
+
+    obstacles
+        .iter()
+        .filter_map(|&center| {
+            let along = (center - position).dot(direction);
+            if along <= 0.0 || along >= length {
+                return None;
+            }
+            let offset = position + direction * along - center;
+            (offset.length() < clearance).then_some((along, center, offset))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map_or(target, |(_, center, offset)| {
+            let side = offset.try_normalize().unwrap_or(direction.perp());
+            center + side * (clearance + AVOID_MARGIN)
+        })
+}
 
 pub fn explorer_movement_system_wasd(
     time: Res<Time>,
-    mut explorer_query: Query<&mut Transform, With<Explorer>>,
-    _roaming: Single<Entity, With<Roaming>>,
+    mut explorer_query: Query<
+        (&mut Explorer, &mut Velocity),
+        (With<Selected>, With<Roaming>, Without<Autopilot>),
+    >,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    reached: Query<&ReachedPlanet>,
 ) {
-    let mut transform = explorer_query.single_mut().unwrap();
-    let speed = 150.0;
     let mut direction = Vec2::ZERO;
 
-    //if keyboard_input.pressed(KeyCode::KeyW) { direction.y += 1.0; }
-    //if keyboard_input.pressed(KeyCode::KeyS) { direction.y -= 1.0; }
-    if keyboard_input.pressed(KeyCode::KeyA) && reached.single().map_or(true, |r| r.0) {
+    if keyboard_input.pressed(KeyCode::KeyW) {
+        direction.y += 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::KeyS) {
+        direction.y -= 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::KeyA) {
         direction.x -= 1.0;
     }
-    if keyboard_input.pressed(KeyCode::KeyD) && !reached.single().is_ok_and(|r| r.0) {
+    if keyboard_input.pressed(KeyCode::KeyD) {
         direction.x += 1.0;
     }
 
-    if direction.length() > 0.0 {
-        let movement = direction.normalize() * speed * time.delta_secs();
-        transform.translation.x += movement.x;
-        transform.translation.y += movement.y;
+    for (mut explorer, mut velocity) in explorer_query.iter_mut() {
+        if direction != Vec2::ZERO {
+            // Taking the controls back cancels any click-to-travel
+            explorer.target_planet = None;
+        } else if explorer.target_planet.is_some() {
+            continue;
+        }
+        let speed = explorer.travel_speed;
+        accelerate(
+            &mut velocity,
+            direction.normalize_or_zero(),
+            speed,
+            time.delta_secs(),
+        );
     }
 }
 
-pub fn check_explorer_reach(
-    mut commands: Commands,
-    explorer_transform: Single<&Transform, With<Explorer>>,
-    planet_query: Query<&Transform, With<Planet>>,
-    reached: Query<Entity, With<ReachedPlanet>>,
-    mut dialog_query: Query<&mut Visibility, With<PlanetDialog>>,
+// Steers towards `Explorer::target_planet` around the other planets and stops at its surface
+pub fn travel_to_target_system(
+    time: Res<Time>,
+    mut explorer_query: Query<
+        (&Transform, &mut Explorer, &mut Velocity, Has<Selected>),
+        With<Roaming>,
+    >,
+    planet_query: Query<(Entity, &Transform), (With<Planet>, Without<Explorer>)>,
 ) {
-    let mut in_range = false;
-    let mut is_left = false;
-
-    for planet_transform in &planet_query {
-        let distance = explorer_transform
-            .translation
-            .distance(planet_transform.translation);
-
-        if distance < 50.0 {
-            is_left = explorer_transform.translation.x < planet_transform.translation.x;
-            in_range = true;
-            break;
+    let delta = time.delta_secs();
+    for (transform, mut explorer, mut velocity, selected) in explorer_query.iter_mut() {
+        let Some(target) = explorer.target_planet else {
+            // The selected explorer is slowed down by the keyboard system instead
+            if !selected {
+                accelerate(&mut velocity, Vec2::ZERO, explorer.travel_speed, delta);
+            }
+            continue;
+        };
+        let Ok((_, planet_transform)) = planet_query.get(target) else {
+            // The planet was destroyed on the way
+            explorer.target_planet = None;
+            continue;
+        };
+        let position = transform.translation.truncate();
+        let destination = planet_transform.translation.truncate();
+        let remaining = destination.distance(position) - COLLISION_DISTANCE;
+        if remaining <= 2.0 {
+            explorer.target_planet = None;
+            velocity.0 = Vec2::ZERO;
+            continue;
         }
+
+        let obstacles: Vec<Vec2> = planet_query
+            .iter()
+            .filter(|&(planet, _)| planet != target)
+            .map(|(_, transform)| transform.translation.truncate())
+            .collect();
+        let waypoint = next_waypoint(position, destination, &obstacles);
+        let direction = (waypoint - position).normalize_or_zero();
+        let speed = explorer.travel_speed * (remaining / BRAKING_DISTANCE).min(1.0);
+        accelerate(&mut velocity, direction, speed, delta);
     }
+}
 
-    if in_range && reached.is_empty() {
-        println!("Explorer reached a planet!");
-        for mut visibility in &mut dialog_query {
-            *visibility = Visibility::Visible;
+// Applies the velocity and keeps the explorers out of the planets
+pub fn integrate_explorer_motion(
+    time: Res<Time>,
+    mut explorer_query: Query<(&mut Transform, &mut Velocity), (With<Explorer>, With<Roaming>)>,
+    planet_query: Query<&Transform, (With<Planet>, Without<Explorer>)>,
+) {
+    for (mut transform, mut velocity) in explorer_query.iter_mut() {
+        let mut position = transform.translation.truncate() + velocity.0 * time.delta_secs();
+        for planet_transform in planet_query.iter() {
+            let center = planet_transform.translation.truncate();
+            let offset = position - center;
+            let distance = offset.length();
+            if distance >= COLLISION_DISTANCE {
+                continue;
+            }
+            let normal = offset.try_normalize().unwrap_or(Vec2::Y);
+            position = center + normal * COLLISION_DISTANCE;
+            // Only the part of the velocity going into the planet is lost
+            let into = velocity.0.dot(normal);
+            if into < 0.0 {
+                velocity.0 -= normal * into;
+            }
         }
-        commands.spawn(ReachedPlanet(is_left));
-    } else if !in_range {
-        for mut visibility in &mut dialog_query {
-            *visibility = Visibility::Hidden;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+// Clicking a planet sends the selected explorer flying to it
+pub fn click_to_travel_system(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<(&Camera, &GlobalTransform)>,
+    interaction_query: Query<&Interaction>,
+    mut explorer_query: Query<&mut Explorer, (With<Selected>, With<Roaming>, Without<Autopilot>)>,
+    planet_query: Query<(Entity, &Transform), With<Planet>>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    // Clicks on the UI are for the buttons
+    if interaction_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    let (camera, camera_transform) = *camera;
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let Ok(cursor) = camera.viewport_to_world_2d(camera_transform, cursor) else {
+        return;
+    };
+    let Some((planet, _)) = planet_query
+        .iter()
+        .find(|(_, transform)| transform.translation.truncate().distance(cursor) <= PLANET_RADIUS)
+    else {
+        return;
+    };
+    for mut explorer in explorer_query.iter_mut() {
+        explorer.target_planet = Some(planet);
+    }
+}
+
+pub fn check_explorer_reach(
+    mut commands: Commands,
+    explorer: Single<
+        (Entity, &Transform, Option<&ReachedPlanet>),
+        (With<Selected>, With<Roaming>, Without<Autopilot>),
+    >,
+    planet_query: Query<(Entity, &Transform), With<Planet>>,
+) {
+    let (explorer, explorer_transform, reached) = *explorer;
+    let position = explorer_transform.translation.truncate();
+
+    let nearest = planet_query
+        .iter()
+        .map(|(planet, transform)| (planet, transform.translation.truncate().distance(position)))
+        .filter(|&(_, distance)| distance < REACH_DISTANCE)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(planet, _)| planet);
+
+    match (nearest, reached) {
+        (Some(planet), Some(reached)) if reached.0 == planet => {}
+        (Some(planet), _) => {
+            info!("Explorer reached a planet!");
+            commands.entity(explorer).insert(ReachedPlanet(planet));
         }
-        if let Ok(entity) = reached.single() {
-            commands.entity(entity).despawn();
+        (None, Some(_)) => {
+            commands
+                .entity(explorer)
+                .remove::<(ReachedPlanet, SkippedPlanet)>();
         }
+        (None, None) => {}
     }
 }
@@ -0,0 +1,493 @@
+use bevy::prelude::*;
+use common_game::components::resource::{BasicResourceType, ComplexResourceType};
+use common_game::protocols::planet_explorer::ExplorerToPlanet;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::explorer::crafting::{Ingredient, can_craft, recipe, send_craft_request, take_request};
+use crate::explorer::handler::ExplorerHandler;
+use crate::explorer::inventory::{COMPLEX_RESOURCE_TYPES, Inventory};
+use crate::explorer::movement::REACH_DISTANCE;
+use crate::explorer::{Docking, Explorer, Landed, request_landing, request_take_off};
+use crate::log::LogEvent;
+use crate::orchestrator::Orchestrator;
+use crate::planet::{Planet, PlanetCell, PlanetId, PlanetUi};
+
+// Seconds between two decisions, leaves time for the planets to answer
+const AUTOPILOT_STEP_SECS: f32 = 0.5;
+// Decisions spent on a planet that does not answer before leaving it
+const PATIENCE: u32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExplorerAction {
+    Wait,
+    TravelTo(Entity),
+    Land(Entity),
+    Generate(BasicResourceType),
+    Craft(ComplexResourceType),
+    TakeOff,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlanetView {
+    pub entity: Entity,
+    pub id: u32,
+    pub position: Vec2,
+    pub charged_cells: usize,
+}
+
+// What a strategy is allowed to know when choosing the next action
+pub struct ExplorerView<'a> {
+    pub position: Vec2,
+    pub landed_on: Option<u32>,
+    pub supported: &'a [BasicResourceType],
+    pub combinations: &'a [ComplexResourceType],
+    pub inventory: &'a Inventory,
+    // Sorted by planet id
+    pub planets: &'a [PlanetView],
+}
+
+impl ExplorerView<'_> {
+    pub fn planet(&self, id: u32) -> Option<&PlanetView> {
+        self.planets.iter().find(|planet| planet.id == id)
+    }
+
+    // Lands when close enough, flies there otherwise
+    pub fn approach(&self, planet: &PlanetView) -> ExplorerAction {
+        if planet.position.distance(self.position) < REACH_DISTANCE {
+            ExplorerAction::Land(planet.entity)
+        } else {
+            ExplorerAction::TravelTo(planet.entity)
+        }
+    }
+}
+
+pub trait ExplorerStrategy: Send + Sync + 'static {
+    fn name(&self) -> String;
+    fn next_action(&mut self, view: &ExplorerView) -> ExplorerAction;
+}
+
+// Visits every planet in turn and generates one resource on each
+#[derive(Default)]
+pub struct RoundRobin {
+    next: usize,
+    turns: u32,
+    generated: bool,
+}
+
+impl ExplorerStrategy for RoundRobin {
+    fn name(&self) -> String {
+        "round robin".to_string()
+    }
+
+    fn next_action(&mut self, view: &ExplorerView) -> ExplorerAction {
+        if view.landed_on.is_some() {
+            self.turns += 1;
+            return match view.supported.first() {
+                Some(&resource) if !self.generated => {
+                    self.generated = true;
+                    ExplorerAction::Generate(resource)
+                }
+                _ if self.turns >= PATIENCE => {
+                    self.turns = 0;
+                    self.generated = false;
+                    self.next += 1;
+                    ExplorerAction::TakeOff
+                }
+                _ => ExplorerAction::Wait,
+            };
+        }
+        if view.planets.is_empty() {
+            return ExplorerAction::Wait;
+        }
+        view.approach(&view.planets[self.next % view.planets.len()])
+    }
+}
+
+// Goes where the most energy cells are charged and drains them
+#[derive(Default)]
+pub struct ChargedCells {
+    left: Option<u32>,
+    turns: u32,
+}
+
+impl ExplorerStrategy for ChargedCells {
+    fn name(&self) -> String {
+        "charged cells".to_string()
+    }
+
+    fn next_action(&mut self, view: &ExplorerView) -> ExplorerAction {
+        if let Some(planet_id) = view.landed_on {
+            let charged = view
+                .planet(planet_id)
+                .map_or(0, |planet| planet.charged_cells);
+            self.turns += 1;
+            return match view.supported.first() {
+                Some(&resource) if charged > 0 => {
+                    self.turns = 0;
+                    ExplorerAction::Generate(resource)
+                }
+                _ if self.turns >= PATIENCE => {
+                    self.turns = 0;
+                    self.left = Some(planet_id);
+                    ExplorerAction::TakeOff
+                }
+                _ => ExplorerAction::Wait,
+            };
+        }
+        let best = view
+            .planets
+            .iter()
+            .filter(|planet| planet.charged_cells > 0 && Some(planet.id) != self.left)
+            .max_by_key(|planet| planet.charged_cells);
+        match best {
+            Some(planet) => view.approach(planet),
+            None => ExplorerAction::Wait,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RecipeStep {
+    Gather(BasicResourceType),
+    Craft(ComplexResourceType),
+}
+
+// The first thing missing to craft `target` from the inventory
+fn next_step(inventory: &Inventory, target: ComplexResourceType) -> RecipeStep {
+    if can_craft(inventory, target) {
+        return RecipeStep::Craft(target);
+    }
+    let [first, second] = recipe(target);
+    for (ingredient, needed) in [(first, 1 + usize::from(first == second)), (second, 1)] {
+        match ingredient {
+            Ingredient::Basic(resource_type) if inventory.basic_count(resource_type) < needed => {
+                return RecipeStep::Gather(resource_type);
+            }
+            Ingredient::Complex(resource_type)
+                if inventory.complex_count(resource_type) < needed =>
+            {
+                return next_step(inventory, resource_type);
+            }
+            _ => {}
+        }
+    }
+    RecipeStep::Craft(target)
+}
+
+// Gathers the ingredients of `target` and crafts it, over and over
+pub struct CollectRecipe {
+    target: ComplexResourceType,
+    // What each visited planet supports
+    known: HashMap<u32, (Vec<BasicResourceType>, Vec<ComplexResourceType>)>,
+    next: usize,
+    turns: u32,
+}
+
+impl CollectRecipe {
+    pub fn new(target: ComplexResourceType) -> Self {
+        Self {
+            target,
+            known: HashMap::new(),
+            next: 0,
+            turns: 0,
+        }
+    }
+
+    fn can_do(&self, planet_id: u32, step: RecipeStep) -> Option<bool> {
+        let (supported, combinations) = self.known.get(&planet_id)?;
+        Some(match step {
+            RecipeStep::Gather(resource_type) => supported.contains(&resource_type),
+            RecipeStep::Craft(resource_type) => combinations.contains(&resource_type),
+        })
+    }
+}
+
+impl ExplorerStrategy for CollectRecipe {
+    fn name(&self) -> String {
+        format!("collect {:?}", self.target)
+    }
+
+    fn next_action(&mut self, view: &ExplorerView) -> ExplorerAction {
+        let step = next_step(view.inventory, self.target);
+
+        if let Some(planet_id) = view.landed_on {
+            self.turns += 1;
+            // Both supported lists have been answered by now
+            if self.turns >= 2 && !self.known.contains_key(&planet_id) {
+                self.known.insert(
+                    planet_id,
+                    (view.supported.to_vec(), view.combinations.to_vec()),
+                );
+            }
+            let charged = view
+                .planet(planet_id)
+                .map_or(0, |planet| planet.charged_cells);
+            if charged == 0 && self.turns >= 2 {
+                // Generating and combining both need a charged energy cell
+                self.turns = 0;
+                return ExplorerAction::TakeOff;
+            }
+            return match (step, self.can_do(planet_id, step)) {
+                (RecipeStep::Gather(resource_type), Some(true)) => {
+                    self.turns = 0;
+                    ExplorerAction::Generate(resource_type)
+                }
+                (RecipeStep::Craft(resource_type), Some(true)) => {
+                    self.turns = 0;
+                    ExplorerAction::Craft(resource_type)
+                }
+                (_, Some(false)) => {
+                    self.turns = 0;
+                    ExplorerAction::TakeOff
+                }
+                (_, None) if self.turns >= PATIENCE => {
+                    self.turns = 0;
+                    ExplorerAction::TakeOff
+                }
+                (_, None) => ExplorerAction::Wait,
+            };
+        }
+
+        self.turns = 0;
+        // A known planet that can do the step, then the ones never visited
+        let planet = view
+            .planets
+            .iter()
+            .find(|planet| planet.charged_cells > 0 && self.can_do(planet.id, step) == Some(true))
+            .or_else(|| {
+                view.planets
+                    .iter()
+                    .find(|planet| !self.known.contains_key(&planet.id))
+            });
+        match planet {
+            Some(planet) => view.approach(planet),
+            None if view.planets.is_empty() => ExplorerAction::Wait,
+            None => {
+                // Nobody can help, keep touring in case a planet answers differently
+                self.next += 1;
+                let planet = &view.planets[self.next % view.planets.len()];
+                self.known.remove(&planet.id);
+                view.approach(planet)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StrategyKind {
+    #[default]
+    Manual,
+    RoundRobin,
+    ChargedCells,
+    CollectRecipe(ComplexResourceType),
+}
+
+impl StrategyKind {
+    pub fn build(self) -> Option<Box<dyn ExplorerStrategy>> {
+        match self {
+            StrategyKind::Manual => None,
+            StrategyKind::RoundRobin => Some(Box::new(RoundRobin::default())),
+            StrategyKind::ChargedCells => Some(Box::new(ChargedCells::default())),
+            StrategyKind::CollectRecipe(target) => Some(Box::new(CollectRecipe::new(target))),
+        }
+    }
+
+    // Order used by the settings screen
+    pub fn next(self) -> Self {
+        match self {
+            StrategyKind::Manual => StrategyKind::RoundRobin,
+            StrategyKind::RoundRobin => StrategyKind::ChargedCells,
+            StrategyKind::ChargedCells => StrategyKind::CollectRecipe(COMPLEX_RESOURCE_TYPES[0]),
+            StrategyKind::CollectRecipe(target) => COMPLEX_RESOURCE_TYPES
+                .iter()
+                .position(|&resource_type| resource_type == target)
+                .and_then(|index| COMPLEX_RESOURCE_TYPES.get(index + 1))
+                .map_or(StrategyKind::Manual, |&next| {
+                    StrategyKind::CollectRecipe(next)
+                }),
+        }
+    }
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrategyKind::Manual => write!(f, "manual"),
+            StrategyKind::RoundRobin => write!(f, "round-robin"),
+            StrategyKind::ChargedCells => write!(f, "charged-cells"),
+            StrategyKind::CollectRecipe(target) => {
+                write!(f, "recipe:{}", format!("{target:?}").to_lowercase())
+            }
+        }
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manual" => Ok(StrategyKind::Manual),
+            "round-robin" => Ok(StrategyKind::RoundRobin),
+            "charged-cells" => Ok(StrategyKind::ChargedCells),
+            _ => {
+                let target = s
+                    .strip_prefix("recipe:")
+                    .and_then(|name| {
+                        COMPLEX_RESOURCE_TYPES.into_iter().find(|resource_type| {
+                            format!("{resource_type:?}").eq_ignore_ascii_case(name)
+                        })
+                    })
+                    .ok_or_else(|| format!("unknown explorer strategy {s}"))?;
+                Ok(StrategyKind::CollectRecipe(target))
+            }
+        }
+    }
+}
+
+// One strategy per explorer, chosen on the command line or in the settings screen and
+// applied when a game starts
+#[derive(Resource, Clone)]
+pub struct ExplorerRoster(pub Vec<StrategyKind>);
+
+impl Default for ExplorerRoster {
+    fn default() -> Self {
+        Self(vec![StrategyKind::Manual])
+    }
+}
+
+impl fmt::Display for ExplorerRoster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, strategy) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{strategy}")?;
+        }
+        Ok(())
+    }
+}
+
+// An explorer with this component ignores the keyboard and the dialogs
+#[derive(Component)]
+pub struct Autopilot {
+    pub strategy: Box<dyn ExplorerStrategy>,
+    timer: Timer,
+}
+
+impl Autopilot {
+    pub fn new(strategy: Box<dyn ExplorerStrategy>) -> Self {
+        Self {
+            strategy,
+            timer: Timer::from_seconds(AUTOPILOT_STEP_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+pub fn autopilot_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut explorer_query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Explorer,
+            &ExplorerHandler,
+            &mut Inventory,
+            &mut Autopilot,
+            Option<&Landed>,
+        ),
+        Without<Docking>,
+    >,
+    planet_query: Query<(Entity, &PlanetId, &Transform), With<Planet>>,
+    ui_query: Query<(&PlanetUi, &Children)>,
+    cell_query: Query<&PlanetCell>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    let charged: HashMap<Entity, usize> = ui_query
+        .iter()
+        .filter_map(|(ui, children)| {
+            children
+                .iter()
+                .find_map(|child| cell_query.get(child).ok())
+                .map(|cell| (ui.0, cell.charged_cell))
+        })
+        .collect();
+    let mut planets: Vec<PlanetView> = planet_query
+        .iter()
+        .map(|(entity, id, transform)| PlanetView {
+            entity,
+            id: id.0,
+            position: transform.translation.truncate(),
+            charged_cells: charged.get(&entity).copied().unwrap_or(0),
+        })
+        .collect();
+    planets.sort_by_key(|planet| planet.id);
+
+    for (entity, transform, mut explorer, handler, mut inventory, mut autopilot, landed) in
+        explorer_query.iter_mut()
+    {
+        if !autopilot.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let view = ExplorerView {
+            position: transform.translation.truncate(),
+            landed_on: handler.landed_on(),
+            supported: &handler.supported,
+            combinations: &handler.combinations,
+            inventory: &inventory,
+            planets: &planets,
+        };
+        let action = autopilot.strategy.next_action(&view);
+        if action != ExplorerAction::Wait {
+            debug!("explorer {} chose {action:?}", handler.id());
+        }
+
+        let result = match action {
+            ExplorerAction::Wait => Ok(()),
+            ExplorerAction::TravelTo(planet) => {
+                explorer.target_planet = Some(planet);
+                Ok(())
+            }
+            ExplorerAction::Land(planet) => {
+                explorer.target_planet = None;
+                match planets.iter().find(|view| view.entity == planet) {
+                    Some(view) if handler.landed_on().is_none() => {
+                        request_landing(&mut commands, entity, handler, planet, view.id, &mut orch)
+                            .map_err(|e| e.to_string())
+                    }
+                    _ => Ok(()),
+                }
+            }
+            ExplorerAction::Generate(resource) => handler
+                .send_to_planet(ExplorerToPlanet::GenerateResourceRequest {
+                    explorer_id: handler.id(),
+                    resource,
+                })
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            ExplorerAction::Craft(target) => match take_request(&mut inventory, target) {
+                Some(request) => send_craft_request(handler, &mut inventory, request)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                None => Err(format!(
+                    "cannot craft {target:?} with the current inventory"
+                )),
+            },
+            ExplorerAction::TakeOff => match landed {
+                Some(landed) => request_take_off(&mut commands, entity, handler, landed, &mut orch)
+                    .map_err(|e| e.to_string()),
+                None => Ok(()),
+            },
+        };
+        if let Err(e) = result {
+            log.write(LogEvent::error(format!(
+                "{} autopilot: {e}",
+                autopilot.strategy.name()
+            )));
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use crate::GameState;
+use crate::log::{LogEvent, LogKind};
+use crate::orchestrator::Orchestrator;
+use crate::orchestrator::factory::{DEFAULT_PLANET_AI, PlanetRegistry};
+use crate::orchestrator::record::{MessageRecorder, RecordPath};
+use crate::planet::*;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::prelude::*;
+use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
+use crossbeam_channel::unbounded;
+use serde::Deserialize;
+
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct Galaxy {
+    pub planets: Vec<PlanetDescription>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PlanetDescription {
+    pub name: String,
+    pub position: (f32, f32),
+    pub sprite: String,
+    pub num_cell: usize,
+    #[serde(default = "default_planet_ai")]
+    pub ai: String,
+}
+
+fn default_planet_ai() -> String {
+    DEFAULT_PLANET_AI.to_string()
+}
+
+#[derive(Resource)]
+pub struct GalaxyHandles {
+    pub playing: Handle<Galaxy>,
+    pub creative: Handle<Galaxy>,
+}
+
+impl GalaxyHandles {
+    pub fn get(&self, state: GameState) -> &Handle<Galaxy> {
+        match state {
+            GameState::Creative => &self.creative,
+            _ => &self.playing,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GalaxyLoader;
+
+#[derive(Debug)]
+pub enum GalaxyLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for GalaxyLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GalaxyLoaderError::Io(e) => write!(f, "could not read galaxy file: {e}"),
+            GalaxyLoaderError::Ron(e) => write!(f, "could not parse galaxy file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for GalaxyLoaderError {}
+
+impl From<std::io::Error> for GalaxyLoaderError {
+    fn from(e: std::io::Error) -> Self {
+        GalaxyLoaderError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for GalaxyLoaderError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        GalaxyLoaderError::Ron(e)
+    }
+}
+
+impl AssetLoader for GalaxyLoader {
+    type Asset = Galaxy;
+    type Settings = ();
+    type Error = GalaxyLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes::<Galaxy>(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["galaxy.ron"]
+    }
+}
+
+pub fn galaxy_plugin(app: &mut App) {
+    app.init_asset::<Galaxy>()
+        .init_asset_loader::<GalaxyLoader>()
+        .init_resource::<PlanetRegistry>()
+        .add_systems(Startup, load_galaxies);
+}
+
+fn load_galaxies(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GalaxyHandles {
+        playing: asset_server.load("galaxies/playing.galaxy.ron"),
+        creative: asset_server.load("galaxies/creative.galaxy.ron"),
+    });
+}
+
+impl Galaxy {
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, GalaxyLoaderError> {
+        let bytes = std::fs::read(path)?;
+        Ok(ron::de::from_bytes::<Galaxy>(&bytes)?)
+    }
+}
+
+impl PlanetDescription {
+    pub fn translation(&self) -> Vec3 {
+        Vec3::new(self.position.0, self.position.1, 0.0)
+    }
+}
+
+// The column of planet state panels, live spawned planets add theirs to it too
+#[derive(Component)]
+pub struct PlanetList;
+
+pub fn spawn_galaxy(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    registry: &PlanetRegistry,
+    galaxy: &Galaxy,
+    record: Option<&RecordPath>,
+    state: GameState,
+) -> Orchestrator {
+    let planet_list = commands
+        .spawn((
+            DespawnOnExit(state),
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: percent(5.0),
+                left: px(20),
+                height: percent(90.0),
+                width: percent(20.0),
+                top: percent(5.0),
+                ..default()
+            },
+            PlanetList,
+        ))
+        .id();
+
+    populate_galaxy(
+        commands,
+        registry,
+        galaxy,
+        record,
+        |commands, id, description| {
+            spawn_planet_visuals(commands, asset_server, planet_list, id, description, state)
+        },
+    )
+}
+
+pub fn spawn_planet_visuals(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    planet_list: Entity,
+    id: u32,
+    description: &PlanetDescription,
+    state: GameState,
+) -> Entity {
+    let planet_entity = commands
+        .spawn(planet(
+            id,
+            &description.name,
+            &description.ai,
+            description.translation(),
+            asset_server.load(&description.sprite),
+            state,
+        ))
+        .id();
+    commands.spawn((
+        planet_state(
+            asset_server,
+            &format!("{} [{}]", description.name, description.ai),
+            planet_entity,
+            PlanetCell {
+                num_cell: description.num_cell,
+                charged_cell: 0,
+            },
+            PlanetRocket(false),
+            state,
+        ),
+        ChildOf(planet_list),
+    ));
+    planet_entity
+}
+
+// Creates the planet thread and registers its channels, it still has to be started
+pub fn launch_planet(
+    orchestrator: &mut Orchestrator,
+    registry: &PlanetRegistry,
+    id: u32,
+    description: &PlanetDescription,
+) -> Result<(), String> {
+    let (orch_tx, orch_rx) = unbounded();
+    let (planet_tx, planet_rx) = unbounded();
+    let (expl_tx, expl_rx) = unbounded();
+    let mut p = registry.create(&description.ai, id, orch_rx, planet_tx, expl_rx)?;
+    orchestrator.add_op_tx(id, orch_tx);
+    orchestrator.add_po_rx(id, planet_rx);
+    orchestrator.add_ep_tx(id, expl_tx);
+    let handle = std::thread::spawn(move || {
+        let _ = p.run();
+    });
+    orchestrator.add_planet_handle(id, handle);
+    Ok(())
+}
+
+// Creates the planet threads and channels, `spawn_planet` decides what entity represents them
+pub fn populate_galaxy(
+    commands: &mut Commands,
+    registry: &PlanetRegistry,
+    galaxy: &Galaxy,
+    record: Option<&RecordPath>,
+    mut spawn_planet: impl FnMut(&mut Commands, u32, &PlanetDescription) -> Entity,
+) -> Orchestrator {
+    let mut orchestrator = Orchestrator::new();
+    if let Some(RecordPath(path)) = record {
+        match MessageRecorder::create(path) {
+            Ok(recorder) => {
+                info!("Recording orchestrator messages to {}", path.display());
+                orchestrator.record_to(recorder);
+            }
+            Err(e) => {
+                error!("Could not create recording {}: {e}", path.display());
+                commands.write_message(LogEvent::error(format!("Recording disabled: {e}")));
+            }
+        }
+    }
+
+    for (id, description) in (0u32..).zip(galaxy.planets.iter()) {
+        if let Err(e) = launch_planet(&mut orchestrator, registry, id, description) {
+            error!("Error creating planet {}: {e}", description.name);
+            commands.write_message(LogEvent::error(format!(
+                "Planet {} could not be created: {e}",
+                description.name
+            )));
+            continue;
+        }
+        spawn_planet(commands, id, description);
+    }
+
+    for i in orchestrator.planet_ids() {
+        if let Err(e) = orchestrator.send_to_planet_id(i, OrchestratorToPlanet::StartPlanetAI) {
+            error!("Failed to start planet {i}: {e}");
+            commands.write_message(LogEvent::planet(
+                LogKind::Error,
+                i,
+                format!("failed to start: {e}"),
+            ));
+        }
+    }
+
+    orchestrator
+}
@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::galaxy_event::EVENT_TRAVEL_SECS;
+use crate::galaxy_event::phenomenon::GalaxyPhenomenon;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    Calm,
+    #[default]
+    Normal,
+    Hard,
+    Brutal,
+}
+
+impl Difficulty {
+    // Added to the asteroid weight for every minute the game has been running
+    pub fn asteroid_ramp_per_minute(self) -> f32 {
+        match self {
+            Difficulty::Calm => 0.0,
+            Difficulty::Normal => 0.25,
+            Difficulty::Hard => 0.5,
+            Difficulty::Brutal => 1.0,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Calm => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Brutal,
+            Difficulty::Brutal => Difficulty::Calm,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct EventWeights {
+    pub sunray: f32,
+    pub asteroid: f32,
+    pub nothing: f32,
+    pub solar_flare: f32,
+    pub meteor_shower: f32,
+    pub supernova: f32,
+    pub black_hole: f32,
+}
+
+impl EventWeights {
+    pub fn phenomenon(&self, phenomenon: GalaxyPhenomenon) -> f32 {
+        match phenomenon {
+            GalaxyPhenomenon::SolarFlare => self.solar_flare,
+            GalaxyPhenomenon::MeteorShower => self.meteor_shower,
+            GalaxyPhenomenon::Supernova => self.supernova,
+            GalaxyPhenomenon::BlackHole => self.black_hole,
+        }
+    }
+}
+
+impl Default for EventWeights {
+    fn default() -> Self {
+        Self {
+            sunray: 1.0,
+            asteroid: 1.0,
+            nothing: 1.0,
+            solar_flare: 0.1,
+            meteor_shower: 0.1,
+            supernova: 0.05,
+            black_hole: 0.05,
+        }
+    }
+}
+
+// Listed before the planet weights by tunable_values, tunable_mut follows the same order
+const TUNABLE_LABELS: [&str; 9] = [
+    "Spawn interval",
+    "Travel time",
+    "Sunray weight",
+    "Asteroid weight",
+    "Nothing weight",
+    "Solar flare weight",
+    "Meteor shower weight",
+    "Supernova weight",
+    "Black hole weight",
+];
+
+// How the random spawner picks events, read from a RON file with `--events`
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GalaxyEventConfig {
+    pub spawn_interval_secs: f32,
+    pub travel_secs: f32,
+    pub weights: EventWeights,
+    // By planet name, planets that are not listed weigh 1
+    pub planet_weights: HashMap<String, f32>,
+    pub difficulty: Difficulty,
+}
+
+impl Default for GalaxyEventConfig {
+    fn default() -> Self {
+        Self {
+            spawn_interval_secs: 1.0,
+            travel_secs: EVENT_TRAVEL_SECS,
+            weights: EventWeights::default(),
+            planet_weights: HashMap::new(),
+            difficulty: Difficulty::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EventConfigError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Invalid { field: String, value: f32 },
+}
+
+impl std::fmt::Display for EventConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventConfigError::Io(e) => write!(f, "could not read event config: {e}"),
+            EventConfigError::Ron(e) => write!(f, "could not parse event config: {e}"),
+            EventConfigError::Invalid { field, value } => {
+                write!(f, "{field} is out of range: {value}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EventConfigError {}
+
+impl From<std::io::Error> for EventConfigError {
+    fn from(e: std::io::Error) -> Self {
+        EventConfigError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for EventConfigError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        EventConfigError::Ron(e)
+    }
+}
+
+impl GalaxyEventConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, EventConfigError> {
+        let bytes = std::fs::read(path)?;
+        let config = ron::de::from_bytes::<GalaxyEventConfig>(&bytes)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // The durations end up in Timer::from_seconds, which panics on negative or NaN values,
+    // weights must be finite and not below zero
+    pub fn validate(&self) -> Result<(), EventConfigError> {
+        let weights = &self.weights;
+        let fields = [
+            ("spawn_interval_secs", self.spawn_interval_secs),
+            ("travel_secs", self.travel_secs),
+            ("weights.sunray", weights.sunray),
+            ("weights.asteroid", weights.asteroid),
+            ("weights.nothing", weights.nothing),
+            ("weights.solar_flare", weights.solar_flare),
+            ("weights.meteor_shower", weights.meteor_shower),
+            ("weights.supernova", weights.supernova),
+            ("weights.black_hole", weights.black_hole),
+        ]
+        .map(|(field, value)| (field.to_string(), value));
+        let planets = self
+            .planet_weights
+            .iter()
+            .map(|(name, value)| (format!("planet_weights[{name}]"), *value));
+        match fields
+            .into_iter()
+            .chain(planets)
+            .find(|(_, value)| !value.is_finite() || *value < 0.0)
+        {
+            Some((field, value)) => Err(EventConfigError::Invalid { field, value }),
+            // A zero interval would spawn on every tick
+            None if self.spawn_interval_secs == 0.0 => Err(EventConfigError::Invalid {
+                field: "spawn_interval_secs".to_string(),
+                value: 0.0,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // Every number the settings screen can tune, in the order it lists them
+    pub fn tunable_values(&self, planets: &[String]) -> Vec<(String, f32)> {
+        let weights = &self.weights;
+        let values = [
+            self.spawn_interval_secs,
+            self.travel_secs,
+            weights.sunray,
+            weights.asteroid,
+            weights.nothing,
+            weights.solar_flare,
+            weights.meteor_shower,
+            weights.supernova,
+            weights.black_hole,
+        ];
+        TUNABLE_LABELS
+            .iter()
+            .zip(values)
+            .map(|(label, value)| (label.to_string(), value))
+            .chain(
+                planets
+                    .iter()
+                    .map(|name| (format!("{name} weight"), self.planet_weight(name))),
+            )
+            .collect()
+    }
+
+    // The value listed at `index` by tunable_values, a planet weight is only stored once edited
+    pub fn tunable_mut(&mut self, index: usize, planets: &[String]) -> Option<&mut f32> {
+        let weights = &mut self.weights;
+        Some(match index {
+            0 => &mut self.spawn_interval_secs,
+            1 => &mut self.travel_secs,
+            2 => &mut weights.sunray,
+            3 => &mut weights.asteroid,
+            4 => &mut weights.nothing,
+            5 => &mut weights.solar_flare,
+            6 => &mut weights.meteor_shower,
+            7 => &mut weights.supernova,
+            8 => &mut weights.black_hole,
+            _ => {
+                let name = planets.get(index - TUNABLE_LABELS.len())?;
+                self.planet_weights.entry(name.clone()).or_insert(1.0)
+            }
+        })
+    }
+
+    pub fn asteroid_weight_at(&self, elapsed_secs: f32) -> f32 {
+        self.weights.asteroid + self.difficulty.asteroid_ramp_per_minute() * elapsed_secs / 60.0
+    }
+
+    pub fn planet_weight(&self, name: &str) -> f32 {
+        self.planet_weights.get(name).copied().unwrap_or(1.0)
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Events every {:.2}s, travel {:.1}s, weights sunray {} / asteroid {} / nothing {} / \
+             flare {} / shower {} / supernova {} / black hole {}, difficulty {:?}",
+            self.spawn_interval_secs,
+            self.travel_secs,
+            self.weights.sunray,
+            self.weights.asteroid,
+            self.weights.nothing,
+            self.weights.solar_flare,
+            self.weights.meteor_shower,
+            self.weights.supernova,
+            self.weights.black_hole,
+            self.difficulty
+        )
+    }
+}
+
+// Index of the chosen weight, None when they are all zero or negative
+pub fn pick_weighted(rng: &mut impl Rng, weights: &[f32]) -> Option<usize> {
+    let total: f32 = weights.iter().map(|weight| weight.max(0.0)).sum();
+    if total <= 0.0 {
+        return None;
+    }
+    let mut roll = rng.random_range(0.0..total);
+    for (i, weight) in weights.iter().enumerate() {
+        let weight = weight.max(0.0);
+        if roll < weight {
+            return Some(i);
+        }
+        roll -= weight;
+    }
+    // Rounding can leave a tiny remainder past the last weight
+    weights.iter().rposition(|weight| *weight > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<GalaxyEventConfig, EventConfigError> {
+        let config = ron::from_str::<GalaxyEventConfig>(source)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[test]
+    fn accepts_the_defaults_and_partial_files() {
+        assert!(GalaxyEventConfig::default().validate().is_ok());
+        let config = parse("(travel_secs: 2.0, weights: (sunray: 3.0))").unwrap();
+        assert_eq!(config.weights.sunray, 3.0);
+        assert_eq!(config.weights.asteroid, 1.0);
+    }
+
+    #[test]
+    fn rejects_values_that_would_panic_or_skew_the_weights() {
+        for source in [
+            "(travel_secs: -1.0)",
+            "(spawn_interval_secs: 0.0)",
+            "(spawn_interval_secs: inf)",
+            "(weights: (asteroid: -0.5))",
+            "(planet_weights: {\"Alpha\": -1.0})",
+        ] {
+            assert!(
+                matches!(parse(source), Err(EventConfigError::Invalid { .. })),
+                "{source} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn tunables_are_listed_and_edited_in_the_same_order() {
+        let planets = ["Alpha".to_string(), "Beta".to_string()];
+        let mut config = GalaxyEventConfig::default();
+        let count = config.tunable_values(&planets).len();
+        assert_eq!(count, TUNABLE_LABELS.len() + planets.len());
+        // Listing does not store the default planet weights
+        assert!(config.planet_weights.is_empty());
+
+        for index in 0..count {
+            *config.tunable_mut(index, &planets).unwrap() = index as f32;
+        }
+        for (index, (_, value)) in config.tunable_values(&planets).into_iter().enumerate() {
+            assert_eq!(value, index as f32);
+        }
+        assert!(config.tunable_mut(count, &planets).is_none());
+    }
+}
@@ -1,17 +1,29 @@
+use crate::log::{LogEvent, LogKind};
 use crate::orchestrator::Orchestrator;
 use bevy::prelude::*;
-use common_game::components::asteroid::Asteroid;
 use common_game::components::sunray::Sunray;
 use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
-use rand::Rng;
 
 use crate::EventSpawnTimer;
 use crate::GameState;
-use crate::LogText;
 use crate::PlanetEntities;
 use crate::planet::*;
+use crate::resources::SimulationRng;
+use config::{GalaxyEventConfig, pick_weighted};
+use phenomenon::{BlackHole, GalaxyPhenomenon};
+use trajectory::{galaxy_edge, launch_asteroid};
 
-#[derive(Component)]
+pub mod config;
+pub mod phenomenon;
+pub mod trajectory;
+
+// Time between an event appearing and reaching its planet
+pub const EVENT_TRAVEL_SECS: f32 = 3.0;
+
+// Alpha lost every second by an approaching event, down to 0.3
+const EVENT_FADE_PER_SEC: f32 = 0.6;
+
+#[derive(Component, Clone, Copy, Debug)]
 pub enum GalaxyEvent {
     Sunray,
     Asteroid,
@@ -30,62 +42,150 @@ pub fn event_spawner_system(
     mut commands: Commands,
     time: Res<Time>,
     mut timer: ResMut<EventSpawnTimer>,
-    planet_query: Query<(Entity, &Name, &PlanetId), With<Planet>>,
-    //mut log_query: Query<&mut Text, With<LogText>>,
+    config: Res<GalaxyEventConfig>,
+    mut rng: ResMut<SimulationRng>,
+    planet_query: Query<(Entity, &Name, &PlanetId, &Transform), With<Planet>>,
+    mut log: MessageWriter<LogEvent>,
 ) {
-    if !timer.0.tick(time.delta()).just_finished() {
+    timer.elapsed += time.delta();
+    if !timer.timer.tick(time.delta()).just_finished() {
         return;
     }
 
-    let mut rng = rand::rng();
-
-    // Choose random planet
-    let planet_idx = rng.random_range(0..planet_query.count());
+    // Query order is not stable across runs, the seeded choice must be
+    let mut planets: Vec<_> = planet_query.iter().collect();
+    if planets.is_empty() {
+        return;
+    }
+    planets.sort_by_key(|(_, _, id, _)| id.0);
+    let rng = &mut rng.0;
+    let positions: Vec<Vec2> = planets
+        .iter()
+        .map(|(_, _, _, transform)| transform.translation.truncate())
+        .collect();
+    let edge = galaxy_edge(&positions);
 
-    let Some((target, name, id)) = planet_query.iter().nth(planet_idx) else {
-        warn!("no planet finded with id {planet_idx}");
+    // Choose a planet, weighted by name
+    let planet_weights: Vec<f32> = planets
+        .iter()
+        .map(|(_, name, _, _)| config.planet_weight(name.as_str()))
+        .collect();
+    let Some(planet_idx) = pick_weighted(rng, &planet_weights) else {
+        warn!("every planet has a zero event weight");
         return;
     };
-    let log_message = match rng.random_range(0..3) {
-        0 => {
+    let (target, name, id, _) = planets[planet_idx];
+
+    // The phenomena come after the three simple outcomes
+    let mut event_weights = vec![
+        config.weights.sunray,
+        config.asteroid_weight_at(timer.elapsed.as_secs_f32()),
+        config.weights.nothing,
+    ];
+    event_weights
+        .extend(GalaxyPhenomenon::ALL.map(|phenomenon| config.weights.phenomenon(phenomenon)));
+    let duration = Timer::from_seconds(config.travel_secs, TimerMode::Once);
+    match pick_weighted(rng, &event_weights) {
+        Some(0) => {
             commands.spawn((
                 DespawnOnExit(GameState::Playing),
                 GalaxyEvent::Sunray,
                 EventTarget {
                     planet: target,
-                    duration: Timer::from_seconds(3.0, TimerMode::Once),
+                    duration,
                 },
             ));
-            format!(" Sunray approaching planet {name}!")
+            info!(" Sunray approaching planet {name}!");
+            log.write(LogEvent::planet(
+                LogKind::Sunray,
+                id.0,
+                " Sunray approaching!",
+            ));
         }
-        1 => {
-            commands.spawn((
-                DespawnOnExit(GameState::Playing),
-                GalaxyEvent::Asteroid,
-                EventTarget {
-                    planet: target,
-                    duration: Timer::from_seconds(3.0, TimerMode::Once),
-                },
+        Some(1) => {
+            launch_asteroid(
+                &mut commands,
+                rng,
+                edge,
+                positions[planet_idx],
+                config.travel_secs,
+                None,
+                GameState::Playing,
+            );
+            info!(" Asteroid approaching planet {name}!");
+            log.write(LogEvent::planet(
+                LogKind::Asteroid,
+                id.0,
+                " Asteroid approaching!",
             ));
-            format!(" Asteroid approaching planet {name}!")
         }
-        _ => "󰒲 Nothing happening this cycle.".to_string(),
-    };
-
-    info!(log_message);
-    // Update UI text instead of printing
-    /*if let Ok(mut text) = log_query.single_mut() {
-        text.0 = format!("\n{}\n{}", log_message, text.0);
-    }*/
+        Some(i) if i >= 3 => {
+            let phenomenon = GalaxyPhenomenon::ALL[i - 3];
+            for wave in phenomenon.waves(rng, planet_idx, planets.len()) {
+                let travel_secs = config.travel_secs + wave.delay_secs;
+                if let GalaxyEvent::Asteroid = wave.event {
+                    launch_asteroid(
+                        &mut commands,
+                        rng,
+                        edge,
+                        positions[wave.planet],
+                        travel_secs,
+                        Some(phenomenon),
+                        GameState::Playing,
+                    );
+                    continue;
+                }
+                let (planet, _, _, _) = planets[wave.planet];
+                commands.spawn((
+                    DespawnOnExit(GameState::Playing),
+                    wave.event,
+                    phenomenon,
+                    EventTarget {
+                        planet,
+                        duration: Timer::from_seconds(travel_secs, TimerMode::Once),
+                    },
+                ));
+            }
+            if phenomenon == GalaxyPhenomenon::BlackHole {
+                commands.spawn((
+                    DespawnOnExit(GameState::Playing),
+                    BlackHole::new(target, config.travel_secs),
+                ));
+            }
+            info!("{phenomenon} approaching planet {name}!");
+            log.write(match phenomenon {
+                GalaxyPhenomenon::Supernova => LogEvent::info(format!(
+                    "{phenomenon}! Every planet is hit by a sunray surge"
+                )),
+                GalaxyPhenomenon::SolarFlare => {
+                    LogEvent::planet(LogKind::Sunray, id.0, format!("{phenomenon} approaching!"))
+                }
+                GalaxyPhenomenon::MeteorShower | GalaxyPhenomenon::BlackHole => LogEvent::planet(
+                    LogKind::Asteroid,
+                    id.0,
+                    format!("{phenomenon} approaching!"),
+                ),
+            });
+        }
+        _ => info!("󰒲 Nothing happening this cycle."),
+    }
 }
 pub fn event_visual_spawn(
     event: On<Add, GalaxyEvent>,
     mut commands: Commands,
-    event_query: Query<(&GalaxyEvent, &EventTarget, Entity), Without<EventVisual>>,
+    event_query: Query<
+        (
+            &GalaxyEvent,
+            &EventTarget,
+            Entity,
+            Option<&GalaxyPhenomenon>,
+        ),
+        Without<EventVisual>,
+    >,
     planet_query: Query<&Transform, With<Planet>>,
 ) {
     // Create visuals for new events
-    let Ok((event_type, target, event_entity)) = event_query.get(event.entity) else {
+    let Ok((event_type, target, event_entity, phenomenon)) = event_query.get(event.entity) else {
         return;
     };
     let Ok(transform) = planet_query.get(target.planet) else {
@@ -95,6 +195,11 @@ pub fn event_visual_spawn(
         GalaxyEvent::Sunray => (Color::srgb(1.0, 1.0, 0.0), Vec2::new(40.0, 40.0)),
         GalaxyEvent::Asteroid => (Color::srgb(0.5, 0.5, 0.5), Vec2::new(35.0, 35.0)),
     };
+    // Waves of a phenomenon are tinted and a bit smaller than lone events
+    let (color, size) = match phenomenon {
+        Some(phenomenon) => (phenomenon.color(), size * 0.75),
+        None => (color, size),
+    };
 
     commands.entity(event_entity).insert((
         Sprite {
@@ -112,29 +217,31 @@ pub fn event_visual_spawn(
 }
 
 pub fn event_visual_move(
+    time: Res<Time>,
     planet_query: Query<&Transform, (With<Planet>, Without<EventVisual>)>,
     mut existing_visuals: Query<(&mut Transform, &mut Sprite), With<EventVisual>>,
 ) {
     // Animate existing visuals (simple bobbing effect)
     for (mut transform, mut sprite) in existing_visuals.iter_mut() {
-        transform.translation.y -= 20.0 * 0.016;
+        transform.translation.y -= 20.0 * time.delta_secs();
 
-        let new_alpha = (sprite.color.alpha() - 0.01).max(0.3);
+        let new_alpha = (sprite.color.alpha() - EVENT_FADE_PER_SEC * time.delta_secs()).max(0.3);
         sprite.color.set_alpha(new_alpha);
     }
 }
 
 // ===== Event Handler System =====
 
+// Only sunrays travel on a timer, asteroids fly as an AsteroidBody and hit whatever they cross
 pub fn event_handler_system(
     mut commands: Commands,
     time: Res<Time>,
-    mut event_query: Query<(&GalaxyEvent, Entity, &mut EventTarget)>,
+    mut event_query: Query<(Entity, &mut EventTarget)>,
     planet_query: Query<&PlanetId, With<Planet>>,
-    //mut log_query: Query<&mut Text, With<LogText>>,
-    orch: Res<Orchestrator>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
 ) {
-    for (event, entity, mut target) in event_query.iter_mut() {
+    for (entity, mut target) in event_query.iter_mut() {
         target.duration.tick(time.delta());
         if !target.duration.just_finished() {
             continue;
@@ -142,21 +249,12 @@ pub fn event_handler_system(
         let Ok(id) = planet_query.get(target.planet) else {
             continue;
         };
-        match event {
-            GalaxyEvent::Sunray => {
-                commands.entity(entity).despawn();
-                orch.send_to_planet_id(id.0, OrchestratorToPlanet::Sunray(Sunray::default()));
-            }
-            GalaxyEvent::Asteroid => {
-                commands.entity(entity).despawn();
-                orch.send_to_planet_id(id.0, OrchestratorToPlanet::Asteroid(Asteroid::default()));
-            }
-        };
-
-        // Update UI text instead of printing
-        /*if let Ok(mut text) = log_query.single_mut() {
-            text.0 = format!("\n{}\n{}", log_message, text.0);
-        }*/
+        commands.entity(entity).despawn();
+        if let Err(e) =
+            orch.send_to_planet_id(id.0, OrchestratorToPlanet::Sunray(Sunray::default()))
+        {
+            log.write(LogEvent::error(e.to_string()));
+        }
     }
 }
 
@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::galaxy_event::GalaxyEvent;
+use crate::planet::Planet;
+
+// Larger events that unfold as several sunrays and asteroids
+#[derive(Component, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GalaxyPhenomenon {
+    SolarFlare,
+    MeteorShower,
+    Supernova,
+    BlackHole,
+}
+
+// One sunray or asteroid of a phenomenon, `planet` indexes the sorted planet list
+pub struct Wave {
+    pub planet: usize,
+    pub event: GalaxyEvent,
+    pub delay_secs: f32,
+}
+
+impl GalaxyPhenomenon {
+    pub const ALL: [GalaxyPhenomenon; 4] = [
+        GalaxyPhenomenon::SolarFlare,
+        GalaxyPhenomenon::MeteorShower,
+        GalaxyPhenomenon::Supernova,
+        GalaxyPhenomenon::BlackHole,
+    ];
+
+    pub fn waves(self, rng: &mut impl Rng, target: usize, planet_count: usize) -> Vec<Wave> {
+        match self {
+            // A burst of sunrays on a single planet
+            GalaxyPhenomenon::SolarFlare => (0..rng.random_range(3..=5))
+                .map(|i| Wave {
+                    planet: target,
+                    event: GalaxyEvent::Sunray,
+                    delay_secs: 0.2 * i as f32,
+                })
+                .collect(),
+            // Asteroids sweeping over the target and its neighbours
+            GalaxyPhenomenon::MeteorShower => (0..planet_count.min(3))
+                .map(|i| Wave {
+                    planet: (target + i) % planet_count,
+                    event: GalaxyEvent::Asteroid,
+                    delay_secs: 0.5 * i as f32,
+                })
+                .collect(),
+            // Every planet gets two sunrays at once
+            GalaxyPhenomenon::Supernova => (0..planet_count)
+                .flat_map(|planet| {
+                    [0.0, 0.5].map(|delay_secs| Wave {
+                        planet,
+                        event: GalaxyEvent::Sunray,
+                        delay_secs,
+                    })
+                })
+                .collect(),
+            // The black hole drags asteroids into the planet it sits next to
+            GalaxyPhenomenon::BlackHole => (0..3)
+                .map(|i| Wave {
+                    planet: target,
+                    event: GalaxyEvent::Asteroid,
+                    delay_secs: BLACK_HOLE_PULL_SECS * i as f32,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            GalaxyPhenomenon::SolarFlare => Color::srgb(1.0, 0.55, 0.0),
+            GalaxyPhenomenon::MeteorShower => Color::srgb(0.6, 0.35, 0.2),
+            GalaxyPhenomenon::Supernova => Color::srgb(0.9, 0.95, 1.0),
+            GalaxyPhenomenon::BlackHole => Color::srgb(0.5, 0.2, 0.7),
+        }
+    }
+}
+
+impl std::fmt::Display for GalaxyPhenomenon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            GalaxyPhenomenon::SolarFlare => "Solar flare",
+            GalaxyPhenomenon::MeteorShower => "Meteor shower",
+            GalaxyPhenomenon::Supernova => "Supernova",
+            GalaxyPhenomenon::BlackHole => "Black hole",
+        };
+        write!(f, "{name}")
+    }
+}
+
+const BLACK_HOLE_PULL_SECS: f32 = 1.0;
+const BLACK_HOLE_OFFSET: Vec2 = Vec2::new(110.0, 60.0);
+
+// Stays next to its planet until the last asteroid it pulled has hit
+#[derive(Component)]
+pub struct BlackHole {
+    pub planet: Entity,
+    pub lifetime: Timer,
+}
+
+impl BlackHole {
+    pub fn new(planet: Entity, travel_secs: f32) -> Self {
+        Self {
+            planet,
+            lifetime: Timer::from_seconds(
+                travel_secs + 2.0 * BLACK_HOLE_PULL_SECS,
+                TimerMode::Once,
+            ),
+        }
+    }
+}
+
+pub fn black_hole_visual(
+    event: On<Add, BlackHole>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    black_hole_query: Query<&BlackHole>,
+    planet_query: Query<&Transform, With<Planet>>,
+) {
+    let Ok(black_hole) = black_hole_query.get(event.entity) else {
+        return;
+    };
+    let Ok(transform) = planet_query.get(black_hole.planet) else {
+        return;
+    };
+    commands.entity(event.entity).insert((
+        Sprite {
+            image: asset_server.load("sprites/Black_hole.png"),
+            custom_size: Some(Vec2::new(90.0, 90.0)),
+            ..default()
+        },
+        Transform::from_translation(
+            (transform.translation.truncate() + BLACK_HOLE_OFFSET).extend(1.5),
+        ),
+    ));
+}
+
+pub fn black_hole_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut black_hole_query: Query<(Entity, &mut BlackHole, Option<&mut Transform>)>,
+) {
+    for (entity, mut black_hole, transform) in black_hole_query.iter_mut() {
+        if black_hole.lifetime.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(mut transform) = transform {
+            transform.rotate_z(-1.5 * time.delta_secs());
+        }
+    }
+}
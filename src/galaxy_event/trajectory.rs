@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use common_game::components::asteroid::Asteroid;
+use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
+use rand::Rng;
+use std::f32::consts::TAU;
+
+use crate::GameState;
+use crate::explorer::movement::EXPLORER_RADIUS;
+use crate::explorer::{Docking, Explorer, Landed};
+use crate::galaxy_event::GalaxyEvent;
+use crate::galaxy_event::phenomenon::GalaxyPhenomenon;
+use crate::log::{LogEvent, LogKind};
+use crate::orchestrator::Orchestrator;
+use crate::planet::*;
+
+pub const ASTEROID_RADIUS: f32 = 15.0;
+// Distance between the outermost planet and the point asteroids are launched from
+const EDGE_MARGIN: f32 = 300.0;
+
+// An asteroid flying through the galaxy, it only hits whatever it actually crosses
+#[derive(Component)]
+pub struct AsteroidBody {
+    pub velocity: Vec2,
+    // Despawned once it has flown past the galaxy without hitting anything
+    pub lifetime: Timer,
+}
+
+// Center and radius of a circle a bit larger than the area covered by the planets
+pub fn galaxy_edge(positions: &[Vec2]) -> (Vec2, f32) {
+    if positions.is_empty() {
+        return (Vec2::ZERO, EDGE_MARGIN);
+    }
+    let center = positions.iter().sum::<Vec2>() / positions.len() as f32;
+    let radius = positions
+        .iter()
+        .map(|position| position.distance(center))
+        .fold(0.0, f32::max);
+    (center, radius + EDGE_MARGIN)
+}
+
+// Launches an asteroid from a random point of the edge, aimed to reach `target` after `travel_secs`
+pub fn launch_asteroid(
+    commands: &mut Commands,
+    rng: &mut impl Rng,
+    edge: (Vec2, f32),
+    target: Vec2,
+    travel_secs: f32,
+    phenomenon: Option<GalaxyPhenomenon>,
+    state: GameState,
+) -> Entity {
+    let (center, radius) = edge;
+    let start = center + Vec2::from_angle(rng.random_range(0.0..TAU)) * radius;
+    let travel_secs = travel_secs.max(0.1);
+    let body = (
+        DespawnOnExit(state),
+        GalaxyEvent::Asteroid,
+        AsteroidBody {
+            velocity: (target - start) / travel_secs,
+            lifetime: Timer::from_seconds(3.0 * travel_secs, TimerMode::Once),
+        },
+        Transform::from_translation(start.extend(2.0)),
+    );
+    // In a single spawn so the visual observer already sees the phenomenon
+    match phenomenon {
+        Some(phenomenon) => commands.spawn((body, phenomenon)).id(),
+        None => commands.spawn(body).id(),
+    }
+}
+
+pub fn asteroid_body_visual(
+    event: On<Add, AsteroidBody>,
+    mut commands: Commands,
+    phenomenon_query: Query<&GalaxyPhenomenon>,
+) {
+    let color = phenomenon_query
+        .get(event.entity)
+        .map_or(Color::srgb(0.5, 0.5, 0.5), |phenomenon| phenomenon.color());
+    commands.entity(event.entity).insert(Sprite {
+        color,
+        custom_size: Some(Vec2::splat(2.0 * ASTEROID_RADIUS)),
+        ..default()
+    });
+}
+
+pub fn asteroid_motion_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut asteroid_query: Query<(Entity, &mut AsteroidBody, &mut Transform), Without<Planet>>,
+    planet_query: Query<(&Transform, &PlanetId), With<Planet>>,
+    explorer_query: Query<
+        (&Transform, &Name),
+        (
+            With<Explorer>,
+            Without<Landed>,
+            Without<Docking>,
+            Without<AsteroidBody>,
+        ),
+    >,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for (entity, mut body, mut transform) in asteroid_query.iter_mut() {
+        transform.translation += (body.velocity * time.delta_secs()).extend(0.0);
+        let position = transform.translation.truncate();
+
+        if let Some((_, name)) = explorer_query.iter().find(|(explorer, _)| {
+            explorer.translation.truncate().distance(position) < EXPLORER_RADIUS + ASTEROID_RADIUS
+        }) {
+            commands.entity(entity).despawn();
+            log.write(LogEvent::info(format!("{name} intercepted an asteroid")));
+            continue;
+        }
+
+        if let Some((_, id)) = planet_query.iter().find(|(planet, _)| {
+            planet.translation.truncate().distance(position) < PLANET_RADIUS + ASTEROID_RADIUS
+        }) {
+            commands.entity(entity).despawn();
+            log.write(LogEvent::planet(
+                LogKind::Asteroid,
+                id.0,
+                "was hit by an asteroid",
+            ));
+            if let Err(e) =
+                orch.send_to_planet_id(id.0, OrchestratorToPlanet::Asteroid(Asteroid::default()))
+            {
+                log.write(LogEvent::error(e.to_string()));
+            }
+            continue;
+        }
+
+        if body.lifetime.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
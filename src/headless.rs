@@ -0,0 +1,147 @@
+use crate::galaxy::*;
+use crate::galaxy_event::config::GalaxyEventConfig;
+use crate::galaxy_event::*;
+use crate::log::LogEvent;
+use crate::orchestrator::Orchestrator;
+use crate::orchestrator::factory::PlanetRegistry;
+use crate::orchestrator::lifecycle::*;
+use crate::orchestrator::pending::*;
+use crate::orchestrator::record::RecordPath;
+use crate::planet::*;
+use crate::replay::*;
+use crate::resources::*;
+use crate::simulation_better::listen_to_planets;
+use crate::stats::*;
+use bevy::prelude::*;
+use std::time::Duration;
+
+pub const DEFAULT_TICKS: u64 = 64 * 120;
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Resource)]
+pub struct HeadlessGalaxy(pub Galaxy);
+
+#[derive(Resource)]
+pub struct HeadlessRun {
+    pub max_ticks: u64,
+    pub ticks: u64,
+}
+
+impl HeadlessRun {
+    pub fn new(max_ticks: u64) -> Self {
+        Self {
+            max_ticks,
+            ticks: 0,
+        }
+    }
+}
+
+pub fn headless_plugin(app: &mut App) {
+    app.add_message::<LogEvent>()
+        .add_plugins((lifecycle_plugin, stats_plugin))
+        .init_resource::<PlanetRegistry>()
+        .init_resource::<PlanetMessageBudget>()
+        .init_resource::<GalaxyEventConfig>()
+        .add_systems(Startup, setup)
+        .add_systems(
+            FixedUpdate,
+            (
+                count_ticks,
+                event_spawner_system.run_if(not(resource_exists::<ReplaySchedule>)),
+                replay_events_system.run_if(resource_exists::<ReplaySchedule>),
+                deliver_recorded_hits_system,
+                event_handler_system,
+                trajectory::asteroid_motion_system,
+                cleanup_events_system,
+                phenomenon::black_hole_system,
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                listen_to_planets,
+                expire_pending_requests,
+                despawn_destroyed_planets,
+                join_killed_planets,
+                print_log_events,
+                finish_run,
+            )
+                .chain(),
+        );
+}
+
+fn setup(
+    mut commands: Commands,
+    registry: Res<PlanetRegistry>,
+    galaxy: Res<HeadlessGalaxy>,
+    seed: Res<SimulationSeed>,
+    record: Option<Res<RecordPath>>,
+    replay: Option<Res<ReplaySchedule>>,
+    event_config: Res<GalaxyEventConfig>,
+) {
+    let orchestrator = populate_galaxy(
+        &mut commands,
+        &registry,
+        &galaxy.0,
+        record.as_deref(),
+        |commands, id, description| {
+            commands
+                .spawn(planet_body(
+                    id,
+                    &description.name,
+                    &description.ai,
+                    description.translation(),
+                ))
+                .id()
+        },
+    );
+
+    println!("Simulation seed: {}", seed.0);
+    if let Some(replay) = replay {
+        println!("Replaying {} recorded galaxy events", replay.event_count());
+    } else {
+        println!("{}", event_config.summary());
+    }
+    commands.insert_resource(orchestrator);
+    commands.insert_resource(GalaxyStats::from_galaxy(&galaxy.0));
+    commands.insert_resource(SimulationRng::from_seed(*seed));
+    commands.insert_resource(EventSpawnTimer::new(event_config.spawn_interval_secs));
+}
+
+fn count_ticks(mut run: ResMut<HeadlessRun>) {
+    run.ticks += 1;
+}
+
+fn print_log_events(mut events: MessageReader<LogEvent>) {
+    for event in events.read() {
+        println!("{event}");
+    }
+}
+
+fn finish_run(
+    time: Res<Time>,
+    run: Res<HeadlessRun>,
+    stats: Res<GalaxyStats>,
+    mut orch: ResMut<Orchestrator>,
+    planet_query: Query<(), With<Planet>>,
+    mut exit: MessageWriter<AppExit>,
+    mut finished: Local<bool>,
+) {
+    if *finished || (run.ticks < run.max_ticks && !planet_query.is_empty()) {
+        return;
+    }
+    *finished = true;
+
+    println!(
+        "Run finished after {} ticks ({:.2}s simulated)",
+        run.ticks,
+        time.elapsed_secs()
+    );
+    print!("{}", stats.summary(time.elapsed_secs()));
+
+    for e in orch.shutdown(SHUTDOWN_TIMEOUT) {
+        warn!("{e} during shutdown");
+    }
+    exit.write(AppExit::Success);
+}
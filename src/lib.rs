@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+pub mod cli;
+pub mod clock;
+pub mod explorer;
+pub mod galaxy;
+pub mod galaxy_event;
+pub mod headless;
+pub mod log;
+pub mod orchestrator;
+pub mod planet;
+pub mod replay;
+pub mod resources;
+pub mod scenario;
+pub mod settings;
+pub mod stats;
+//mod simulation;
+pub mod creative;
+pub mod simulation_better;
+pub mod theme;
+
+use crate::explorer::Explorer;
+use crate::planet::Planet;
+use crate::resources::EventSpawnTimer;
+use crate::resources::PlanetEntities;
+
+// Enum that will be used as a global state for the game
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum GameState {
+    #[default]
+    Settings,
+    Creative,
+    Playing,
+}
+
+#[derive(Component)]
+pub struct LogScreen;
+#[derive(Component)]
+pub struct LogText;
+
+#[derive(Component)]
+pub struct PlanetAlphaState;
+#[derive(Component)]
+pub struct PlanetCell;
+#[derive(Component)]
+pub struct PlanetRocket;
+#[derive(Component)]
+pub struct PlanetBetaState;
+#[derive(Component)]
+pub struct PlanetBetaCell;
+#[derive(Component)]
+pub struct PlanetBetaRocket;
+
+#[derive(Component)]
+pub struct PlanetDialog;
+#[derive(Component)]
+pub struct LandedPlanetDialog;
+
+// Marker components for buttons
+#[derive(Component)]
+pub struct YesButton;
+
+#[derive(Component)]
+pub struct NoButton;
+
+#[derive(Component)]
+pub struct ScanButton;
+
+#[derive(Component)]
+pub struct SupportedResourceButton;
+
+#[derive(Component)]
+pub struct ExtractResourceButton;
+
+#[derive(Component)]
+pub struct AvailableEnergyCellButton;
+
+#[derive(Component)]
+pub struct TakeOffPlanetButton;
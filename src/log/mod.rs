@@ -0,0 +1,193 @@
+use crate::GameState;
+use crate::LogScreen;
+use crate::LogText;
+use crate::planet::PlanetId;
+use crate::theme;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+pub const MAX_LOG_LINES: usize = 200;
+const LINE_SCROLL: f32 = 20.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogKind {
+    Info,
+    Sunray,
+    Asteroid,
+    Deflected,
+    Destroyed,
+    Error,
+}
+
+impl LogKind {
+    pub fn color(self) -> Color {
+        match self {
+            LogKind::Info => theme::color::TEXT,
+            LogKind::Sunray => theme::color::SUNRAY,
+            LogKind::Asteroid => theme::color::ASTEROID,
+            LogKind::Deflected => theme::color::DEFLECTED,
+            LogKind::Destroyed => theme::color::DESTROYED,
+            LogKind::Error => theme::color::ERROR,
+        }
+    }
+}
+
+#[derive(Message, Clone, Debug)]
+pub struct LogEvent {
+    pub kind: LogKind,
+    pub planet_id: Option<u32>,
+    pub message: String,
+}
+
+impl LogEvent {
+    pub fn info(message: impl Into<String>) -> Self {
+        Self {
+            kind: LogKind::Info,
+            planet_id: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            kind: LogKind::Error,
+            planet_id: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn planet(kind: LogKind, planet_id: u32, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            planet_id: Some(planet_id),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for LogEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.planet_id {
+            Some(planet_id) => write!(f, "[planet {planet_id}] {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+pub fn log_plugin(app: &mut App) {
+    app.add_message::<LogEvent>().add_systems(
+        Update,
+        (write_log_system, scroll_log_system)
+            .run_if(in_state(GameState::Playing).or(in_state(GameState::Creative))),
+    );
+}
+
+pub fn log_screen(state: GameState) -> impl Bundle {
+    let padding = 12.0;
+
+    (
+        DespawnOnExit(state),
+        Node {
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            top: percent(5.0),
+            right: percent(2.0),
+            width: percent(25.0),
+            height: percent(40.0),
+            padding: UiRect::all(px(padding)),
+            overflow: Overflow::scroll_y(),
+            ..default()
+        },
+        LogScreen,
+        Interaction::None,
+        ScrollPosition::default(),
+        Visibility::Visible,
+        theme::background_color(),
+    )
+}
+
+// Newest line first, the oldest lines are dropped past MAX_LOG_LINES
+fn write_log_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    mut events: MessageReader<LogEvent>,
+    screen: Single<(Entity, Option<&Children>), With<LogScreen>>,
+    planet_query: Query<(&PlanetId, &Name)>,
+    mut planet_names: Local<HashMap<u32, String>>,
+) {
+    // Remember names so lines about despawned planets still show them
+    for (id, name) in planet_query.iter() {
+        planet_names
+            .entry(id.0)
+            .or_insert_with(|| name.as_str().to_string());
+    }
+
+    let events: Vec<&LogEvent> = events.read().collect();
+    if events.is_empty() {
+        return;
+    }
+    let events = &events[events.len().saturating_sub(MAX_LOG_LINES)..];
+    let (screen, children) = *screen;
+
+    let font = theme::basic_font(&asset_server);
+    let mut lines = Vec::with_capacity(events.len());
+    for event in events.iter().rev() {
+        let mut line = commands.spawn((
+            Text::new(format!("{:>6.1}s ", time.elapsed_secs())),
+            font.clone(),
+            TextColor(theme::color::MUTED),
+            LogText,
+        ));
+        line.with_children(|spans| {
+            if let Some(planet_id) = event.planet_id {
+                let name = planet_names
+                    .get(&planet_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("planet {planet_id}"));
+                spans.spawn((
+                    TextSpan::new(format!("[{name}] ")),
+                    font.clone(),
+                    TextColor(theme::color::planet(planet_id)),
+                ));
+            }
+            spans.spawn((
+                TextSpan::new(event.message.clone()),
+                font.clone(),
+                TextColor(event.kind.color()),
+            ));
+        });
+        lines.push(line.id());
+    }
+    commands.entity(screen).insert_children(0, &lines);
+
+    let Some(children) = children else {
+        return;
+    };
+    let overflow = (children.len() + lines.len()).saturating_sub(MAX_LOG_LINES);
+    for old in children.iter().rev().take(overflow) {
+        commands.entity(old).despawn();
+    }
+}
+
+fn scroll_log_system(
+    mut wheel: MessageReader<MouseWheel>,
+    mut screen_query: Query<(&Interaction, &mut ScrollPosition), With<LogScreen>>,
+) {
+    let dy: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * LINE_SCROLL,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+    if dy == 0.0 {
+        return;
+    }
+    for (interaction, mut scroll) in screen_query.iter_mut() {
+        if *interaction != Interaction::None {
+            scroll.y = (scroll.y - dy).max(0.0);
+        }
+    }
+}
@@ -1,94 +1,92 @@
 use bevy::{
     diagnostic::{
-        FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin, SystemInformationDiagnosticsPlugin,
+        Diagnostic, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin, RegisterDiagnostic,
+        SystemInformationDiagnosticsPlugin,
     },
     prelude::*,
 };
-mod explorer;
-mod galaxy_event;
-mod orchestrator;
-mod planet;
-mod resources;
-mod settings;
-//mod simulation;
-mod creative;
-mod simulation_better;
-mod theme;
+use demo::*;
 
-use crate::explorer::Explorer;
-use crate::planet::Planet;
-use crate::resources::EventSpawnTimer;
-use crate::resources::PlanetEntities;
+fn main() -> AppExit {
+    let args = match cli::CliArgs::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            return AppExit::error();
+        }
+    };
+    let seed = resources::SimulationSeed(args.seed.unwrap_or_else(rand::random));
 
-fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
+    let mut app = App::new();
+    if let Some(path) = args.record {
+        app.insert_resource(orchestrator::record::RecordPath(path.into()));
+    }
+    if let Some(path) = args.scenario {
+        match scenario::ScenarioSchedule::from_file(&path) {
+            Ok(schedule) => {
+                app.insert_resource(schedule);
+            }
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                return AppExit::error();
+            }
+        }
+    }
+    if let Some(secs) = args.poll {
+        app.insert_resource(orchestrator::polling::StatePolling::every(secs));
+    }
+    if let Some(path) = args.events {
+        match galaxy_event::config::GalaxyEventConfig::from_file(&path) {
+            Ok(config) => {
+                app.insert_resource(config);
+            }
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                return AppExit::error();
+            }
+        }
+    }
+    if !args.explorers.is_empty() {
+        app.insert_resource(explorer::strategy::ExplorerRoster(args.explorers));
+    }
+    if let Some(path) = args.replay {
+        match replay::ReplaySchedule::from_file(&path) {
+            Ok(schedule) => {
+                app.insert_resource(schedule);
+            }
+            Err(e) => {
+                eprintln!("{path}: {e}");
+                return AppExit::error();
+            }
+        }
+    }
+
+    app.add_plugins(DefaultPlugins)
         //.add_plugins(SystemInformationDiagnosticsPlugin)
         // Adds frame time diagnostics
         .add_plugins(FrameTimeDiagnosticsPlugin::default())
         // Adds a system that prints diagnostics to the console
         .add_plugins(LogDiagnosticsPlugin::default())
+        .register_diagnostic(Diagnostic::new(orchestrator::PLANET_BACKLOG).with_suffix(" messages"))
+        .init_resource::<resources::PlanetMessageBudget>()
+        .insert_resource(seed)
         .init_state::<GameState>()
         .add_systems(Startup, setup)
         .add_plugins((
+            galaxy::galaxy_plugin,
+            log::log_plugin,
+            orchestrator::lifecycle::lifecycle_plugin,
+            stats::stats_plugin,
             settings::settings_plugin,
             simulation_better::simulation_better_plugin,
+            explorer::explorer_plugin,
             creative::creative_plugin,
+            planet::inspector::inspector_plugin,
+            clock::clock_plugin,
         ))
-        .run();
-}
-
-// Enum that will be used as a global state for the game
-#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
-enum GameState {
-    #[default]
-    Settings,
-    Creative,
-    Playing,
+        .run()
 }
 
-#[derive(Component)]
-pub struct LogScreen;
-#[derive(Component)]
-pub struct LogText;
-
-#[derive(Component)]
-pub struct PlanetAlphaState;
-#[derive(Component)]
-pub struct PlanetCell;
-#[derive(Component)]
-pub struct PlanetRocket;
-#[derive(Component)]
-pub struct PlanetBetaState;
-#[derive(Component)]
-pub struct PlanetBetaCell;
-#[derive(Component)]
-pub struct PlanetBetaRocket;
-
-#[derive(Component)]
-struct PlanetDialog;
-#[derive(Component)]
-struct LandedPlanetDialog;
-
-// Marker components for buttons
-#[derive(Component)]
-struct YesButton;
-
-#[derive(Component)]
-struct NoButton;
-
-#[derive(Component)]
-struct SupportedResourceButton;
-
-#[derive(Component)]
-struct ExtractResourceButton;
-
-#[derive(Component)]
-struct AvailableEnergyCellButton;
-
-#[derive(Component)]
-struct TakeOffPlanetButton;
-
 fn setup(mut commands: Commands) {
     // Camera
     commands.spawn((
@@ -0,0 +1,28 @@
+use std::fmt;
+
+#[derive(Debug)]
+pub enum OrchestratorError {
+    UnknownPlanet(u32),
+    Disconnected(u32),
+    Timeout(u32),
+    UnexpectedReply { planet_id: u32, reply: String },
+    JoinPanic(u32),
+}
+
+impl fmt::Display for OrchestratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrchestratorError::UnknownPlanet(id) => write!(f, "planet {id} is not known"),
+            OrchestratorError::Disconnected(id) => {
+                write!(f, "the channel to planet {id} is disconnected")
+            }
+            OrchestratorError::Timeout(id) => write!(f, "planet {id} did not answer in time"),
+            OrchestratorError::UnexpectedReply { planet_id, reply } => {
+                write!(f, "planet {planet_id} sent an unexpected reply: {reply}")
+            }
+            OrchestratorError::JoinPanic(id) => write!(f, "the thread of planet {id} panicked"),
+        }
+    }
+}
+
+impl std::error::Error for OrchestratorError {}
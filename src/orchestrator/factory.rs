@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use common_game::components::planet::Planet;
+use common_game::protocols::orchestrator_planet::*;
+use common_game::protocols::planet_explorer::*;
+use crossbeam_channel::{Receiver, Sender};
+use std::collections::HashMap;
+
+pub const DEFAULT_PLANET_AI: &str = "trip";
+
+pub trait PlanetFactory: Send + Sync + 'static {
+    fn create(
+        &self,
+        id: u32,
+        orch_rx: Receiver<OrchestratorToPlanet>,
+        planet_tx: Sender<PlanetToOrchestrator>,
+        expl_rx: Receiver<ExplorerToPlanet>,
+    ) -> Result<Planet, String>;
+}
+
+impl<F> PlanetFactory for F
+where
+    F: Fn(
+            u32,
+            Receiver<OrchestratorToPlanet>,
+            Sender<PlanetToOrchestrator>,
+            Receiver<ExplorerToPlanet>,
+        ) -> Result<Planet, String>
+        + Send
+        + Sync
+        + 'static,
+{
+    fn create(
+        &self,
+        id: u32,
+        orch_rx: Receiver<OrchestratorToPlanet>,
+        planet_tx: Sender<PlanetToOrchestrator>,
+        expl_rx: Receiver<ExplorerToPlanet>,
+    ) -> Result<Planet, String> {
+        self(id, orch_rx, planet_tx, expl_rx)
+    }
+}
+
+#[derive(Resource)]
+pub struct PlanetRegistry {
+    factories: HashMap<String, Box<dyn PlanetFactory>>,
+}
+
+impl PlanetRegistry {
+    pub fn new() -> Self {
+        Self {
+            factories: HashMap::new(),
+        }
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, factory: impl PlanetFactory) -> &mut Self {
+        let name = name.into();
+        if self
+            .factories
+            .insert(name.clone(), Box::new(factory))
+            .is_some()
+        {
+            warn!("planet AI {name} was already registered, replacing it");
+        }
+        self
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    pub fn create(
+        &self,
+        name: &str,
+        id: u32,
+        orch_rx: Receiver<OrchestratorToPlanet>,
+        planet_tx: Sender<PlanetToOrchestrator>,
+        expl_rx: Receiver<ExplorerToPlanet>,
+    ) -> Result<Planet, String> {
+        let Some(factory) = self.factories.get(name) else {
+            let available = self.names().collect::<Vec<_>>().join(", ");
+            return Err(format!(
+                "no planet AI registered under the name {name} (available: {available})"
+            ));
+        };
+        factory.create(id, orch_rx, planet_tx, expl_rx)
+    }
+}
+
+impl Default for PlanetRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(DEFAULT_PLANET_AI, trip_planet);
+        registry
+    }
+}
+
+fn trip_planet(
+    id: u32,
+    orch_rx: Receiver<OrchestratorToPlanet>,
+    planet_tx: Sender<PlanetToOrchestrator>,
+    expl_rx: Receiver<ExplorerToPlanet>,
+) -> Result<Planet, String> {
+    trip::trip(id, orch_rx, planet_tx, expl_rx).map_err(|e| format!("{e:?}"))
+}
@@ -0,0 +1,100 @@
+use crate::GameState;
+use crate::log::LogEvent;
+use crate::orchestrator::Orchestrator;
+use crate::orchestrator::pending::RequestTimedOut;
+use crate::orchestrator::polling::{StatePolling, poll_planet_states};
+use crate::orchestrator::record::advance_recording_clock;
+use crate::planet::*;
+use bevy::prelude::*;
+use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
+use std::time::Duration;
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+// An asteroid hit a planet that had no rocket to deflect it
+#[derive(Message)]
+pub struct PlanetDestroyed {
+    pub planet_id: u32,
+}
+
+// The planet answered KillPlanet and its thread is about to return
+#[derive(Message)]
+pub struct PlanetKilled {
+    pub planet_id: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transit {
+    Incoming,
+    Outgoing,
+}
+
+// A planet answered an explorer landing on or leaving it
+#[derive(Message)]
+pub struct ExplorerTransit {
+    pub planet_id: u32,
+    pub explorer_id: u32,
+    pub direction: Transit,
+    pub res: Result<(), String>,
+}
+
+pub fn lifecycle_plugin(app: &mut App) {
+    app.add_message::<PlanetDestroyed>()
+        .add_message::<PlanetKilled>()
+        .add_message::<RequestTimedOut>()
+        .add_message::<ExplorerTransit>()
+        .init_resource::<StatePolling>()
+        .add_systems(
+            FixedFirst,
+            advance_recording_clock.run_if(resource_exists::<Orchestrator>),
+        )
+        .add_systems(
+            Update,
+            poll_planet_states.run_if(resource_exists::<Orchestrator>),
+        )
+        .add_systems(OnExit(GameState::Playing), shutdown_planets)
+        .add_systems(OnExit(GameState::Creative), shutdown_planets);
+}
+
+pub fn despawn_destroyed_planets(
+    mut commands: Commands,
+    mut destroyed: MessageReader<PlanetDestroyed>,
+    mut orch: ResMut<Orchestrator>,
+    planet_query: Query<(&PlanetId, Entity), With<Planet>>,
+    ui_query: Query<(Entity, &PlanetUi)>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for PlanetDestroyed { planet_id } in destroyed.read() {
+        if let Some((_, planet_entity)) = planet_query.iter().find(|&(id, _)| id.0 == *planet_id) {
+            if let Some((ui_entity, _)) = ui_query.iter().find(|&(_, ui)| ui.0 == planet_entity) {
+                commands.entity(ui_entity).despawn();
+            }
+            commands.entity(planet_entity).despawn();
+        }
+        if let Err(e) = orch.send_to_planet_id(*planet_id, OrchestratorToPlanet::KillPlanet) {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+}
+
+pub fn join_killed_planets(
+    mut killed: MessageReader<PlanetKilled>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for PlanetKilled { planet_id } in killed.read() {
+        if let Err(e) = orch.join_planet_id(*planet_id) {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+}
+
+fn shutdown_planets(mut commands: Commands, orch: Option<ResMut<Orchestrator>>) {
+    let Some(mut orch) = orch else {
+        return;
+    };
+    for e in orch.shutdown(SHUTDOWN_TIMEOUT) {
+        warn!("{e} during shutdown");
+    }
+    commands.remove_resource::<Orchestrator>();
+}
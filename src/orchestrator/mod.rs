@@ -0,0 +1,263 @@
+use bevy::diagnostic::{DiagnosticPath, Diagnostics};
+use bevy::prelude::*;
+use common_game::components::planet::Planet;
+use common_game::protocols::orchestrator_planet::*;
+use common_game::protocols::planet_explorer::ExplorerToPlanet;
+use crossbeam_channel::*;
+use std::collections::HashMap;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+pub mod error;
+pub mod factory;
+pub mod lifecycle;
+pub mod pending;
+pub mod polling;
+pub mod record;
+
+pub use error::OrchestratorError;
+use pending::*;
+use record::{Direction, MessageRecorder};
+
+pub const PLANET_BACKLOG: DiagnosticPath = DiagnosticPath::const_new("orchestrator/planet_backlog");
+
+pub struct PlanetReply {
+    pub planet_id: u32,
+    pub msg: PlanetToOrchestrator,
+    pub request: Option<PendingRequest>,
+}
+
+#[derive(Resource)]
+pub struct Orchestrator {
+    orch_tx: HashMap<u32, Sender<OrchestratorToPlanet>>,
+    planet_rx: HashMap<u32, Receiver<PlanetToOrchestrator>>,
+    planet_handle: HashMap<u32, JoinHandle<()>>,
+    explorer_tx: HashMap<u32, Sender<ExplorerToPlanet>>,
+    pending: PendingRequests,
+    recorder: Option<MessageRecorder>,
+    planet_id: u32,
+}
+
+impl Orchestrator {
+    pub fn new() -> Self {
+        Self {
+            orch_tx: HashMap::new(),
+            planet_rx: HashMap::new(),
+            planet_handle: HashMap::new(),
+            explorer_tx: HashMap::new(),
+            pending: PendingRequests::default(),
+            recorder: None,
+            planet_id: 0,
+        }
+    }
+
+    pub fn add_op_tx(&mut self, id: u32, tx: Sender<OrchestratorToPlanet>) {
+        self.orch_tx.insert(id, tx);
+    }
+    pub fn add_po_rx(&mut self, id: u32, rx: Receiver<PlanetToOrchestrator>) {
+        self.planet_rx.insert(id, rx);
+    }
+    pub fn add_planet_handle(&mut self, id: u32, handle: JoinHandle<()>) {
+        self.planet_handle.insert(id, handle);
+    }
+    pub fn add_ep_tx(&mut self, id: u32, tx: Sender<ExplorerToPlanet>) {
+        self.explorer_tx.insert(id, tx);
+    }
+
+    // Handed to an explorer once the planet accepted it
+    pub fn explorer_sender(&self, id: u32) -> Option<Sender<ExplorerToPlanet>> {
+        self.explorer_tx.get(&id).cloned()
+    }
+
+    pub fn record_to(&mut self, recorder: MessageRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    pub fn advance_recording_clock(&mut self, delta: Duration) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.advance(delta);
+        }
+    }
+
+    pub fn planet_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self.orch_tx.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    pub fn join_planet_id(&mut self, id: u32) -> Result<(), OrchestratorError> {
+        self.planet_rx.remove(&id);
+        self.orch_tx.remove(&id);
+        self.explorer_tx.remove(&id);
+        self.pending.forget_planet(id);
+        let handle = self
+            .planet_handle
+            .remove(&id)
+            .ok_or(OrchestratorError::UnknownPlanet(id))?;
+        match handle.join() {
+            Ok(()) => {
+                info!("planet {id} joined successfully");
+                Ok(())
+            }
+            Err(e) => {
+                error!("and error {:?} occurred while joining the planet {id}", e);
+                Err(OrchestratorError::JoinPanic(id))
+            }
+        }
+    }
+
+    pub fn shutdown(&mut self, timeout: Duration) -> Vec<OrchestratorError> {
+        let deadline = Instant::now() + timeout;
+        let ids = self.planet_ids();
+        let mut errors = Vec::new();
+
+        for &id in &ids {
+            for msg in [
+                OrchestratorToPlanet::StopPlanetAI,
+                OrchestratorToPlanet::KillPlanet,
+            ] {
+                if let Err(e) = self.send_to_planet_id(id, msg) {
+                    errors.push(e);
+                    break;
+                }
+            }
+        }
+
+        for &id in &ids {
+            let Some(rx) = self.planet_rx.remove(&id) else {
+                continue;
+            };
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match rx.recv_timeout(remaining) {
+                    Ok(msg) => {
+                        if let Some(recorder) = &mut self.recorder {
+                            recorder.record(id, Direction::FromPlanet, &msg);
+                        }
+                        if matches!(msg, PlanetToOrchestrator::KillPlanetResult { .. }) {
+                            break;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {
+                        errors.push(OrchestratorError::Timeout(id));
+                        break;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            self.orch_tx.remove(&id);
+            self.explorer_tx.remove(&id);
+            self.pending.forget_planet(id);
+        }
+
+        for id in ids {
+            let Some(handle) = self.planet_handle.remove(&id) else {
+                continue;
+            };
+            while !handle.is_finished() && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            if !handle.is_finished() {
+                warn!("planet {id} thread is still running after shutdown, detaching it");
+                errors.push(OrchestratorError::Timeout(id));
+                continue;
+            }
+            match handle.join() {
+                Ok(()) => info!("planet {id} joined successfully"),
+                Err(e) => {
+                    error!("and error {:?} occurred while joining the planet {id}", e);
+                    errors.push(OrchestratorError::JoinPanic(id));
+                }
+            }
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.flush();
+        }
+        errors
+    }
+
+    pub fn send_to_planet_id(
+        &mut self,
+        id: u32,
+        msg: OrchestratorToPlanet,
+    ) -> Result<RequestId, OrchestratorError> {
+        info!("attempting to send message {:?} to planet {id}", &msg);
+        let tx = self
+            .orch_tx
+            .get(&id)
+            .ok_or(OrchestratorError::UnknownPlanet(id))?;
+        let kind = ReplyKind::expected_for(&msg);
+        let description = self.recorder.is_some().then(|| format!("{msg:?}"));
+        match tx.send(msg) {
+            Ok(()) => {
+                info!("Sended message to planet {id}");
+                if let (Some(recorder), Some(description)) = (&mut self.recorder, description) {
+                    recorder.record_formatted(id, Direction::ToPlanet, description);
+                }
+                Ok(self.pending.register(id, kind))
+            }
+            Err(e) => {
+                warn!(
+                    "an error {:?} occurred while sending message to planet {id}",
+                    e
+                );
+                Err(OrchestratorError::Disconnected(id))
+            }
+        }
+    }
+
+    // Reads at most `budget` messages from every planet, oldest planet id first
+    pub fn drain_planets(&mut self, budget: usize) -> Vec<Result<PlanetReply, OrchestratorError>> {
+        let mut replies = Vec::new();
+        let mut disconnected = Vec::new();
+        let mut ids: Vec<u32> = self.planet_rx.keys().copied().collect();
+        ids.sort_unstable();
+        for planet_id in ids {
+            let rx = &self.planet_rx[&planet_id];
+            for _ in 0..budget {
+                match rx.try_recv() {
+                    Ok(msg) => {
+                        if let Some(recorder) = &mut self.recorder {
+                            recorder.record(planet_id, Direction::FromPlanet, &msg);
+                        }
+                        let request = self.pending.resolve(planet_id, &msg);
+                        replies.push(Ok(PlanetReply {
+                            planet_id,
+                            msg,
+                            request,
+                        }));
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        disconnected.push(planet_id);
+                        break;
+                    }
+                }
+            }
+        }
+        // Report a dead channel only once, the thread is joined afterwards
+        for planet_id in disconnected {
+            self.planet_rx.remove(&planet_id);
+            self.pending.forget_planet(planet_id);
+            replies.push(Err(OrchestratorError::Disconnected(planet_id)));
+        }
+        replies
+    }
+
+    pub fn backlog(&self) -> usize {
+        self.planet_rx.values().map(Receiver::len).sum()
+    }
+
+    pub fn expire_requests(&mut self, now: Instant) -> Vec<PendingRequest> {
+        self.pending.expire(now)
+    }
+
+    pub fn is_waiting_for(&self, planet_id: u32, kind: ReplyKind) -> bool {
+        self.pending.is_waiting_for(planet_id, kind)
+    }
+}
+
+pub fn planet_backlog_diagnostic(mut diagnostics: Diagnostics, orch: Res<Orchestrator>) {
+    diagnostics.add_measurement(&PLANET_BACKLOG, || orch.backlog() as f64);
+}
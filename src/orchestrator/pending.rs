@@ -0,0 +1,269 @@
+use crate::log::{LogEvent, LogKind};
+use crate::orchestrator::Orchestrator;
+use bevy::prelude::*;
+use common_game::protocols::orchestrator_planet::*;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReplyKind {
+    SunrayAck,
+    AsteroidAck,
+    StartPlanetAIResult,
+    StopPlanetAIResult,
+    KillPlanetResult,
+    InternalStateResponse,
+    IncomingExplorerResponse { explorer_id: u32 },
+    OutgoingExplorerResponse { explorer_id: u32 },
+}
+
+impl ReplyKind {
+    pub fn expected_for(msg: &OrchestratorToPlanet) -> Self {
+        match msg {
+            OrchestratorToPlanet::Sunray(_) => ReplyKind::SunrayAck,
+            OrchestratorToPlanet::Asteroid(_) => ReplyKind::AsteroidAck,
+            OrchestratorToPlanet::StartPlanetAI => ReplyKind::StartPlanetAIResult,
+            OrchestratorToPlanet::StopPlanetAI => ReplyKind::StopPlanetAIResult,
+            OrchestratorToPlanet::KillPlanet => ReplyKind::KillPlanetResult,
+            OrchestratorToPlanet::InternalStateRequest => ReplyKind::InternalStateResponse,
+            OrchestratorToPlanet::IncomingExplorerRequest { explorer_id, .. } => {
+                ReplyKind::IncomingExplorerResponse {
+                    explorer_id: *explorer_id,
+                }
+            }
+            OrchestratorToPlanet::OutgoingExplorerRequest { explorer_id } => {
+                ReplyKind::OutgoingExplorerResponse {
+                    explorer_id: *explorer_id,
+                }
+            }
+        }
+    }
+
+    // `None` for `Stopped`, which answers whatever request a stopped planet refused
+    pub fn of(msg: &PlanetToOrchestrator) -> Option<Self> {
+        match msg {
+            PlanetToOrchestrator::SunrayAck { .. } => Some(ReplyKind::SunrayAck),
+            PlanetToOrchestrator::AsteroidAck { .. } => Some(ReplyKind::AsteroidAck),
+            PlanetToOrchestrator::StartPlanetAIResult { .. } => {
+                Some(ReplyKind::StartPlanetAIResult)
+            }
+            PlanetToOrchestrator::StopPlanetAIResult { .. } => Some(ReplyKind::StopPlanetAIResult),
+            PlanetToOrchestrator::KillPlanetResult { .. } => Some(ReplyKind::KillPlanetResult),
+            PlanetToOrchestrator::InternalStateResponse { .. } => {
+                Some(ReplyKind::InternalStateResponse)
+            }
+            PlanetToOrchestrator::IncomingExplorerResponse { explorer_id, .. } => {
+                Some(ReplyKind::IncomingExplorerResponse {
+                    explorer_id: *explorer_id,
+                })
+            }
+            PlanetToOrchestrator::OutgoingExplorerResponse { explorer_id, .. } => {
+                Some(ReplyKind::OutgoingExplorerResponse {
+                    explorer_id: *explorer_id,
+                })
+            }
+            PlanetToOrchestrator::Stopped { .. } => None,
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        match self {
+            ReplyKind::SunrayAck | ReplyKind::AsteroidAck => Duration::from_secs(1),
+            ReplyKind::InternalStateResponse => Duration::from_millis(500),
+            _ => Duration::from_secs(2),
+        }
+    }
+
+    // Lifecycle requests are still answered by a stopped planet
+    fn answered_when_stopped(&self) -> bool {
+        matches!(
+            self,
+            ReplyKind::StartPlanetAIResult
+                | ReplyKind::StopPlanetAIResult
+                | ReplyKind::KillPlanetResult
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RequestId(u64);
+
+#[derive(Debug, Clone)]
+pub struct PendingRequest {
+    pub id: RequestId,
+    pub planet_id: u32,
+    pub kind: ReplyKind,
+    pub sent_at: Instant,
+    pub deadline: Instant,
+}
+
+#[derive(Default)]
+pub struct PendingRequests {
+    next_id: u64,
+    pending: HashMap<u32, VecDeque<PendingRequest>>,
+}
+
+impl PendingRequests {
+    pub fn register(&mut self, planet_id: u32, kind: ReplyKind) -> RequestId {
+        let id = RequestId(self.next_id);
+        self.next_id += 1;
+        let sent_at = Instant::now();
+        self.pending
+            .entry(planet_id)
+            .or_default()
+            .push_back(PendingRequest {
+                id,
+                planet_id,
+                kind,
+                sent_at,
+                deadline: sent_at + kind.timeout(),
+            });
+        id
+    }
+
+    // Matches a reply with the oldest request of the same kind sent to that planet
+    pub fn resolve(
+        &mut self,
+        planet_id: u32,
+        reply: &PlanetToOrchestrator,
+    ) -> Option<PendingRequest> {
+        let queue = self.pending.get_mut(&planet_id)?;
+        let position = match ReplyKind::of(reply) {
+            Some(kind) => queue.iter().position(|request| request.kind == kind),
+            None => queue
+                .iter()
+                .position(|request| !request.kind.answered_when_stopped()),
+        }?;
+        queue.remove(position)
+    }
+
+    pub fn expire(&mut self, now: Instant) -> Vec<PendingRequest> {
+        let mut expired = Vec::new();
+        for queue in self.pending.values_mut() {
+            queue.retain(|request| {
+                if request.deadline <= now {
+                    expired.push(request.clone());
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        expired
+    }
+
+    pub fn is_waiting_for(&self, planet_id: u32, kind: ReplyKind) -> bool {
+        self.pending
+            .get(&planet_id)
+            .is_some_and(|queue| queue.iter().any(|request| request.kind == kind))
+    }
+
+    pub fn forget_planet(&mut self, planet_id: u32) {
+        self.pending.remove(&planet_id);
+    }
+}
+
+#[derive(Message)]
+pub struct RequestTimedOut {
+    pub request: PendingRequest,
+}
+
+pub fn expire_pending_requests(
+    mut orch: ResMut<Orchestrator>,
+    mut timed_out: MessageWriter<RequestTimedOut>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for request in orch.expire_requests(Instant::now()) {
+        warn!(
+            "request {:?} to planet {} timed out waiting for {:?}",
+            request.id, request.planet_id, request.kind
+        );
+        log.write(LogEvent::planet(
+            LogKind::Error,
+            request.planet_id,
+            format!("did not answer with {:?} in time", request.kind),
+        ));
+        timed_out.write(RequestTimedOut { request });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_the_oldest_request_of_the_same_kind() {
+        let mut requests = PendingRequests::default();
+        let first = requests.register(1, ReplyKind::SunrayAck);
+        requests.register(1, ReplyKind::InternalStateResponse);
+        let second = requests.register(1, ReplyKind::SunrayAck);
+
+        let reply = PlanetToOrchestrator::SunrayAck { planet_id: 1 };
+        assert_eq!(requests.resolve(1, &reply).unwrap().id, first);
+        assert_eq!(requests.resolve(1, &reply).unwrap().id, second);
+        assert!(requests.resolve(1, &reply).is_none());
+        // Replies are matched per planet
+        requests.register(2, ReplyKind::SunrayAck);
+        assert!(requests.resolve(3, &reply).is_none());
+        assert!(requests.is_waiting_for(1, ReplyKind::InternalStateResponse));
+    }
+
+    #[test]
+    fn stopped_skips_the_requests_a_stopped_planet_still_answers() {
+        let mut requests = PendingRequests::default();
+        requests.register(1, ReplyKind::StartPlanetAIResult);
+        requests.register(1, ReplyKind::StopPlanetAIResult);
+        requests.register(1, ReplyKind::KillPlanetResult);
+        let refused = requests.register(1, ReplyKind::InternalStateResponse);
+        requests.register(1, ReplyKind::AsteroidAck);
+
+        let stopped = PlanetToOrchestrator::Stopped { planet_id: 1 };
+        assert_eq!(requests.resolve(1, &stopped).unwrap().id, refused);
+        assert_eq!(
+            requests.resolve(1, &stopped).unwrap().kind,
+            ReplyKind::AsteroidAck
+        );
+        assert!(requests.resolve(1, &stopped).is_none());
+        assert!(requests.is_waiting_for(1, ReplyKind::StartPlanetAIResult));
+        assert!(requests.is_waiting_for(1, ReplyKind::StopPlanetAIResult));
+        assert!(requests.is_waiting_for(1, ReplyKind::KillPlanetResult));
+    }
+
+    #[test]
+    fn expires_requests_at_their_deadline() {
+        let mut requests = PendingRequests::default();
+        let state = requests.register(1, ReplyKind::InternalStateResponse);
+        requests.register(1, ReplyKind::KillPlanetResult);
+        let deadline = requests.pending[&1][0].deadline;
+
+        assert!(
+            requests
+                .expire(deadline - Duration::from_millis(1))
+                .is_empty()
+        );
+        let expired = requests.expire(deadline);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, state);
+        assert!(!requests.is_waiting_for(1, ReplyKind::InternalStateResponse));
+        assert!(requests.is_waiting_for(1, ReplyKind::KillPlanetResult));
+    }
+
+    #[test]
+    fn forgets_every_request_of_a_planet() {
+        let mut requests = PendingRequests::default();
+        requests.register(1, ReplyKind::SunrayAck);
+        requests.register(1, ReplyKind::KillPlanetResult);
+        requests.register(2, ReplyKind::SunrayAck);
+
+        requests.forget_planet(1);
+        assert!(!requests.is_waiting_for(1, ReplyKind::SunrayAck));
+        assert!(!requests.is_waiting_for(1, ReplyKind::KillPlanetResult));
+        assert!(requests.is_waiting_for(2, ReplyKind::SunrayAck));
+        assert_eq!(
+            requests
+                .expire(Instant::now() + Duration::from_secs(10))
+                .len(),
+            1
+        );
+    }
+}
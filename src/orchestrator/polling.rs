@@ -0,0 +1,48 @@
+use crate::log::LogEvent;
+use crate::orchestrator::Orchestrator;
+use crate::orchestrator::pending::ReplyKind;
+use crate::planet::inspector::{AiStatus, PlanetReport};
+use crate::planet::*;
+use bevy::prelude::*;
+use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
+
+pub const DEFAULT_POLL_SECS: f32 = 1.0;
+
+// How often every running planet is asked for its internal state, set with `--poll`
+#[derive(Resource)]
+pub struct StatePolling(pub Timer);
+
+impl StatePolling {
+    pub fn every(secs: f32) -> Self {
+        Self(Timer::from_seconds(secs, TimerMode::Repeating))
+    }
+}
+
+impl Default for StatePolling {
+    fn default() -> Self {
+        Self::every(DEFAULT_POLL_SECS)
+    }
+}
+
+pub fn poll_planet_states(
+    time: Res<Time>,
+    mut polling: ResMut<StatePolling>,
+    mut orch: ResMut<Orchestrator>,
+    planet_query: Query<(&PlanetId, &PlanetReport), With<Planet>>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    if !polling.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    for (id, report) in planet_query.iter() {
+        // A stopped planet would only answer Stopped, a slow one still owes the last answer
+        if report.ai == AiStatus::Stopped
+            || orch.is_waiting_for(id.0, ReplyKind::InternalStateResponse)
+        {
+            continue;
+        }
+        if let Err(e) = orch.send_to_planet_id(id.0, OrchestratorToPlanet::InternalStateRequest) {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::orchestrator::Orchestrator;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Where the orchestrator messages of every game are written, set with --record
+#[derive(Resource, Clone)]
+pub struct RecordPath(pub PathBuf);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    ToPlanet,
+    FromPlanet,
+}
+
+// One line of the recording, `time` is in seconds since the galaxy was populated
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedMessage {
+    pub time: f64,
+    pub planet_id: u32,
+    pub direction: Direction,
+    pub kind: String,
+    pub message: String,
+}
+
+impl RecordedMessage {
+    pub fn new(time: Duration, planet_id: u32, direction: Direction, message: String) -> Self {
+        let kind = message
+            .split([' ', '(', '{'])
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Self {
+            time: time.as_secs_f64(),
+            planet_id,
+            direction,
+            kind,
+            message,
+        }
+    }
+
+    pub fn time(&self) -> Duration {
+        Duration::from_secs_f64(self.time)
+    }
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(std::io::Error),
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+}
+
+impl std::fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingError::Io(e) => write!(f, "could not read recording: {e}"),
+            RecordingError::Json { line, error } => {
+                write!(f, "could not parse recording line {line}: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RecordingError {}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(e: std::io::Error) -> Self {
+        RecordingError::Io(e)
+    }
+}
+
+pub fn read_recording(path: impl AsRef<Path>) -> Result<Vec<RecordedMessage>, RecordingError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut messages = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message = serde_json::from_str(&line).map_err(|error| RecordingError::Json {
+            line: index + 1,
+            error,
+        })?;
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+// Appends every message passing through the orchestrator to a JSON lines file
+pub struct MessageRecorder {
+    writer: Option<BufWriter<File>>,
+    elapsed: Duration,
+}
+
+impl MessageRecorder {
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self {
+            writer: Some(BufWriter::new(File::create(path)?)),
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    pub fn advance(&mut self, delta: Duration) {
+        self.elapsed += delta;
+    }
+
+    pub fn record(&mut self, planet_id: u32, direction: Direction, msg: &impl Debug) {
+        self.record_formatted(planet_id, direction, format!("{msg:?}"));
+    }
+
+    // For messages that are moved into a channel before it is known whether they were sent
+    pub fn record_formatted(&mut self, planet_id: u32, direction: Direction, message: String) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+        let line = RecordedMessage::new(self.elapsed, planet_id, direction, message);
+        let result = serde_json::to_writer(&mut *writer, &line)
+            .map_err(std::io::Error::from)
+            .and_then(|()| writer.write_all(b"\n"));
+        if let Err(e) = result {
+            // Stop recording instead of failing on every message
+            warn!("could not write to the recording, disabling it: {e}");
+            self.writer = None;
+        }
+    }
+
+    pub fn flush(&mut self) {
+        if let Some(writer) = &mut self.writer
+            && let Err(e) = writer.flush()
+        {
+            warn!("could not flush the recording: {e}");
+        }
+    }
+}
+
+// Recording time follows the fixed timestep so a replay lines up with the original ticks
+pub fn advance_recording_clock(time: Res<Time>, mut orch: ResMut<Orchestrator>) {
+    orch.advance_recording_clock(time.delta());
+}
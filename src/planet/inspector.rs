@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use common_game::components::resource::{BasicResourceType, ComplexResourceType};
+use std::collections::VecDeque;
+
+use crate::planet::*;
+use crate::stats::{AsteroidDeflected, SunrayAcked};
+
+// Number of state reports kept for the charged cells sparkline
+const HISTORY_LEN: usize = 24;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AiStatus {
+    #[default]
+    Unknown,
+    Started,
+    Stopped,
+}
+
+// Everything the planet told us about itself, kept on the planet entity
+#[derive(Component, Default)]
+pub struct PlanetReport {
+    // Planet AI named in the galaxy file, the reported state carries no planet type
+    pub ai_name: String,
+    pub cells: usize,
+    pub charged_cells: usize,
+    // In the order the planet reported them, None until the first report
+    pub energy_cells: Option<Vec<bool>>,
+    pub has_rocket: bool,
+    pub ai: AiStatus,
+    pub last_event: Option<String>,
+    // Learnt by the explorers that asked while landed
+    pub supported: Option<Vec<BasicResourceType>>,
+    pub combinations: Option<Vec<ComplexResourceType>>,
+    pub charge_history: VecDeque<usize>,
+}
+
+impl PlanetReport {
+    pub fn new(ai_name: &str) -> Self {
+        Self {
+            ai_name: ai_name.to_string(),
+            ..default()
+        }
+    }
+
+    pub fn record_state(
+        &mut self,
+        energy_cells: Vec<bool>,
+        charged_cells: usize,
+        has_rocket: bool,
+    ) {
+        self.cells = energy_cells.len();
+        self.charged_cells = charged_cells;
+        self.energy_cells = Some(energy_cells);
+        self.has_rocket = has_rocket;
+        if self.charge_history.len() == HISTORY_LEN {
+            self.charge_history.pop_front();
+        }
+        self.charge_history.push_back(charged_cells);
+    }
+
+    pub fn sparkline(&self) -> String {
+        let top = self.cells.max(1);
+        self.charge_history
+            .iter()
+            .map(|&charged| SPARKS[charged.min(top) * (SPARKS.len() - 1) / top])
+            .collect()
+    }
+
+    pub fn details(&self) -> String {
+        let Some(energy_cells) = &self.energy_cells else {
+            return format!(
+                "AI: {} ({:?})\nWaiting for the first state report",
+                self.ai_name, self.ai
+            );
+        };
+        // Cell by cell, unlike the card that puts the charged ones first
+        let cells: String = energy_cells
+            .iter()
+            .map(|&charged| if charged { "󰁹 " } else { "󰁺 " })
+            .collect();
+        let known =
+            |list: Option<String>| list.unwrap_or_else(|| "unknown, land to ask".to_string());
+        // One line per field of the reported state, then what we learnt elsewhere
+        format!(
+            "AI: {} ({:?})\nEnergy cells: {cells}\nCharged cells: {} of {}\nRocket: {}\n\
+             Generates: {}\nCombines: {}\nLast event: {}\nCharge: {}",
+            self.ai_name,
+            self.ai,
+            self.charged_cells,
+            self.cells,
+            if self.has_rocket { "ready" } else { "none" },
+            known(self.supported.as_ref().map(|list| format!("{list:?}"))),
+            known(self.combinations.as_ref().map(|list| format!("{list:?}"))),
+            self.last_event.as_deref().unwrap_or("none"),
+            self.sparkline(),
+        )
+    }
+}
+
+// Hidden under each planet card until the card is clicked
+#[derive(Component)]
+pub struct PlanetInspector(pub Entity);
+
+pub fn inspector_plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            toggle_planet_inspector,
+            record_planet_events,
+            update_planet_inspector,
+        )
+            .chain(),
+    );
+}
+
+fn toggle_planet_inspector(
+    mut card_query: Query<
+        (&Interaction, &mut Node, &Children),
+        (Changed<Interaction>, With<PlanetUi>),
+    >,
+    mut inspector_query: Query<&mut Node, (With<PlanetInspector>, Without<PlanetUi>)>,
+) {
+    for (interaction, mut card, children) in card_query.iter_mut() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        for child in children.iter() {
+            let Ok(mut inspector) = inspector_query.get_mut(child) else {
+                continue;
+            };
+            let expand = inspector.display == Display::None;
+            inspector.display = if expand { Display::Flex } else { Display::None };
+            card.height = if expand { Val::Auto } else { CARD_HEIGHT };
+        }
+    }
+}
+
+fn record_planet_events(
+    time: Res<Time>,
+    mut sunrays: MessageReader<SunrayAcked>,
+    mut deflected: MessageReader<AsteroidDeflected>,
+    mut report_query: Query<(&PlanetId, &mut PlanetReport)>,
+) {
+    let events = sunrays
+        .read()
+        .map(|SunrayAcked { planet_id }| (*planet_id, "absorbed a sunray"))
+        .chain(
+            deflected
+                .read()
+                .map(|AsteroidDeflected { planet_id }| (*planet_id, "deflected an asteroid")),
+        );
+    for (planet_id, event) in events {
+        if let Some((_, mut report)) = report_query.iter_mut().find(|(id, _)| id.0 == planet_id) {
+            report.last_event = Some(format!("{event} at {:.1}s", time.elapsed_secs()));
+        }
+    }
+}
+
+fn update_planet_inspector(
+    report_query: Query<&PlanetReport, Changed<PlanetReport>>,
+    mut inspector_query: Query<(&mut Text, &PlanetInspector)>,
+) {
+    for (mut text, inspector) in inspector_query.iter_mut() {
+        if let Ok(report) = report_query.get(inspector.0) {
+            text.0 = report.details();
+        }
+    }
+}
@@ -1,6 +1,13 @@
 use crate::GameState;
 use crate::theme;
 use bevy::prelude::*;
+use inspector::{PlanetInspector, PlanetReport};
+
+pub mod inspector;
+
+pub const PLANET_RADIUS: f32 = 50.0;
+// Height of a collapsed planet card, it grows to fit the inspector when expanded
+pub const CARD_HEIGHT: Val = Val::Percent(15.0);
 
 #[derive(Component)]
 pub struct Planet;
@@ -24,18 +31,33 @@ pub fn cell_string(cell: &PlanetCell) -> String {
     cells
 }
 
-pub fn planet(id: u32, name: &str, position: Vec3, image: Handle<Image>) -> impl Bundle {
+pub fn planet(
+    id: u32,
+    name: &str,
+    ai: &str,
+    position: Vec3,
+    image: Handle<Image>,
+    state: GameState,
+) -> impl Bundle {
     (
-        DespawnOnExit(GameState::Playing),
+        DespawnOnExit(state),
         Sprite {
             image: image,
-            custom_size: Some(Vec2::new(100.0, 100.0)),
+            custom_size: Some(Vec2::splat(PLANET_RADIUS * 2.0)),
             ..default()
         },
+        planet_body(id, name, ai, position),
+    )
+}
+
+// The planet without any visual, used by the headless runner
+pub fn planet_body(id: u32, name: &str, ai: &str, position: Vec3) -> impl Bundle {
+    (
         Name::new(name.to_string()),
         Transform::from_translation(position),
         PlanetId(id),
         Planet,
+        PlanetReport::new(ai),
     )
 }
 
@@ -45,13 +67,13 @@ pub fn planet_state(
     planet: Entity,
     cell: PlanetCell,
     rocket: PlanetRocket,
+    state: GameState,
 ) -> impl Bundle {
     let padding = 12.0;
     let width = 90.0;
-    let height = 15.0;
 
     (
-        DespawnOnExit(GameState::Playing),
+        DespawnOnExit(state),
         Node {
             flex_direction: FlexDirection::Column,
             //top: top,
@@ -59,10 +81,11 @@ pub fn planet_state(
             //left: left,
             padding: UiRect::all(Val::Px(padding)),
             width: Val::Percent(width),
-            height: Val::Percent(height),
+            height: CARD_HEIGHT,
             ..default()
         },
         //state,
+        Button,
         PlanetUi(planet),
         Visibility::Visible,
         theme::background_color(),
@@ -93,6 +116,16 @@ pub fn planet_state(
                 theme::basic_font(asset_server),
                 theme::text_color(),
                 rocket,
+            ),
+            (
+                Node {
+                    display: Display::None,
+                    ..default()
+                },
+                Text::new(""),
+                theme::basic_font(asset_server),
+                theme::text_color(),
+                PlanetInspector(planet),
             )
         ],
     )
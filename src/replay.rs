@@ -0,0 +1,151 @@
+use crate::GameState;
+use crate::galaxy_event::*;
+use crate::log::{LogEvent, LogKind};
+use crate::orchestrator::Orchestrator;
+use crate::orchestrator::record::*;
+use crate::planet::*;
+use bevy::prelude::*;
+use common_game::components::asteroid::Asteroid;
+use common_game::protocols::orchestrator_planet::OrchestratorToPlanet;
+use std::path::Path;
+use std::time::Duration;
+
+struct ReplayedEvent {
+    time: Duration,
+    planet_id: u32,
+    event: GalaxyEvent,
+}
+
+// Galaxy events read back from a recording, they replace the random spawner when present
+#[derive(Resource)]
+pub struct ReplaySchedule {
+    events: Vec<ReplayedEvent>,
+    next: usize,
+    elapsed: Duration,
+}
+
+impl ReplaySchedule {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        let events = read_recording(path)?
+            .into_iter()
+            .filter(|recorded| recorded.direction == Direction::ToPlanet)
+            .filter_map(|recorded| {
+                let event = match recorded.kind.as_str() {
+                    "Sunray" => GalaxyEvent::Sunray,
+                    "Asteroid" => GalaxyEvent::Asteroid,
+                    _ => return None,
+                };
+                Some(ReplayedEvent {
+                    time: recorded.time(),
+                    planet_id: recorded.planet_id,
+                    event,
+                })
+            })
+            .collect();
+        Ok(Self {
+            events,
+            next: 0,
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    pub fn restart(&mut self) {
+        self.next = 0;
+        self.elapsed = Duration::ZERO;
+    }
+
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+}
+
+// Spawns every recorded event early enough that the handler sends it on its original tick
+pub fn replay_events_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut schedule: ResMut<ReplaySchedule>,
+    planet_query: Query<(Entity, &Name, &PlanetId), With<Planet>>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    let schedule = &mut *schedule;
+    schedule.elapsed += time.delta();
+    let horizon = schedule.elapsed + Duration::from_secs_f32(EVENT_TRAVEL_SECS);
+
+    while let Some(replayed) = schedule.events.get(schedule.next) {
+        if replayed.time > horizon {
+            break;
+        }
+        schedule.next += 1;
+
+        let Some((target, name, _)) = planet_query
+            .iter()
+            .find(|&(_, _, id)| id.0 == replayed.planet_id)
+        else {
+            log.write(LogEvent::planet(
+                LogKind::Error,
+                replayed.planet_id,
+                format!("is gone, replay skipped {:?}", replayed.event),
+            ));
+            continue;
+        };
+        // The handlers tick the timer in the same frame it is spawned
+        let duration = Timer::new(
+            replayed.time.saturating_sub(schedule.elapsed) + time.delta(),
+            TimerMode::Once,
+        );
+        match replayed.event {
+            GalaxyEvent::Sunray => commands.spawn((
+                DespawnOnExit(GameState::Playing),
+                GalaxyEvent::Sunray,
+                EventTarget {
+                    planet: target,
+                    duration,
+                },
+            )),
+            GalaxyEvent::Asteroid => commands.spawn((
+                DespawnOnExit(GameState::Playing),
+                RecordedAsteroidHit {
+                    planet: target,
+                    duration,
+                },
+            )),
+        };
+        info!("Replaying {:?} towards planet {name}", replayed.event);
+    }
+}
+
+// An asteroid the recording saw hitting its planet. It has no trajectory, the hit is
+// delivered on the recorded tick so the replay stays identical whatever crosses its way
+#[derive(Component)]
+pub struct RecordedAsteroidHit {
+    planet: Entity,
+    duration: Timer,
+}
+
+pub fn deliver_recorded_hits_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut hit_query: Query<(Entity, &mut RecordedAsteroidHit)>,
+    planet_query: Query<&PlanetId, With<Planet>>,
+    mut orch: ResMut<Orchestrator>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    for (entity, mut hit) in hit_query.iter_mut() {
+        if !hit.duration.tick(time.delta()).just_finished() {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        let Ok(id) = planet_query.get(hit.planet) else {
+            continue;
+        };
+        if let Err(e) =
+            orch.send_to_planet_id(id.0, OrchestratorToPlanet::Asteroid(Asteroid::default()))
+        {
+            log.write(LogEvent::error(e.to_string()));
+        }
+    }
+}
@@ -1,9 +1,49 @@
 use bevy::prelude::Entity;
 use bevy::prelude::Resource;
 use bevy::prelude::Timer;
+use bevy::prelude::TimerMode;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::time::Duration;
 
 #[derive(Resource, Default)]
-pub struct EventSpawnTimer(pub(crate) Timer);
+pub struct EventSpawnTimer {
+    pub(crate) timer: Timer,
+    // Game time since the spawner started, drives the difficulty ramp
+    pub(crate) elapsed: Duration,
+}
+
+impl EventSpawnTimer {
+    pub fn new(interval_secs: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(interval_secs, TimerMode::Repeating),
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SimulationSeed(pub u64);
+
+// Reseeded from SimulationSeed every time a game starts
+#[derive(Resource)]
+pub struct SimulationRng(pub StdRng);
+
+impl SimulationRng {
+    pub fn from_seed(seed: SimulationSeed) -> Self {
+        Self(StdRng::seed_from_u64(seed.0))
+    }
+}
+
+// Maximum number of messages read from each planet channel per frame
+#[derive(Resource)]
+pub struct PlanetMessageBudget(pub usize);
+
+impl Default for PlanetMessageBudget {
+    fn default() -> Self {
+        Self(64)
+    }
+}
 
 #[derive(Resource)]
 pub struct PlanetEntities {
@@ -0,0 +1,334 @@
+use crate::GameState;
+use crate::galaxy_event::config::GalaxyEventConfig;
+use crate::galaxy_event::trajectory::{galaxy_edge, launch_asteroid};
+use crate::galaxy_event::*;
+use crate::log::{LogEvent, LogKind};
+use crate::planet::*;
+use crate::resources::SimulationRng;
+use bevy::prelude::*;
+use std::path::Path;
+use std::time::Duration;
+
+// A scenario is a list of steps separated by `;` or new lines, `#` starts a comment:
+//   t=2.0 sunray Alpha; t=3.5 asteroid Alpha x3
+// `t` is the time in seconds at which the events reach the planet.
+struct ScenarioStep {
+    time: Duration,
+    event: GalaxyEvent,
+    planet: String,
+    count: u32,
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(std::io::Error),
+    Parse { step: usize, message: String },
+}
+
+impl std::fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScenarioError::Io(e) => write!(f, "could not read scenario: {e}"),
+            ScenarioError::Parse { step, message } => {
+                write!(f, "invalid scenario step {step}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<std::io::Error> for ScenarioError {
+    fn from(e: std::io::Error) -> Self {
+        ScenarioError::Io(e)
+    }
+}
+
+fn parse_step(entry: &str) -> Result<ScenarioStep, String> {
+    let mut tokens: Vec<&str> = entry.split_whitespace().collect();
+
+    // A trailing `x3` repeats the event, anything else is part of the planet name
+    let repeat = tokens
+        .last()
+        .and_then(|token| token.strip_prefix('x'))
+        .and_then(|count| count.parse::<u32>().ok());
+    let count = match repeat {
+        Some(count) if tokens.len() > 3 => {
+            tokens.pop();
+            count
+        }
+        _ => 1,
+    };
+    let [time, event, planet @ ..] = tokens.as_slice() else {
+        return Err("expected `t=<seconds> <event> <planet>`".to_string());
+    };
+    if planet.is_empty() {
+        return Err("missing the planet name".to_string());
+    }
+    let seconds = time
+        .strip_prefix("t=")
+        .and_then(|seconds| seconds.parse::<f32>().ok())
+        .ok_or_else(|| format!("{time} is not a time like t=2.5"))?;
+    let time = Duration::try_from_secs_f32(seconds)
+        .map_err(|e| format!("{time} is not a valid time: {e}"))?;
+    let event = match event.to_lowercase().as_str() {
+        "sunray" => GalaxyEvent::Sunray,
+        "asteroid" => GalaxyEvent::Asteroid,
+        other => return Err(format!("unknown event {other}")),
+    };
+
+    Ok(ScenarioStep {
+        time,
+        event,
+        planet: planet.join(" "),
+        count,
+    })
+}
+
+// Creative mode timeline, with its own clock so it can be paused, stepped and rewound
+#[derive(Resource)]
+pub struct ScenarioSchedule {
+    steps: Vec<ScenarioStep>,
+    next: usize,
+    elapsed: Duration,
+    pub paused: bool,
+}
+
+impl ScenarioSchedule {
+    pub fn parse(source: &str) -> Result<Self, ScenarioError> {
+        let mut steps = source
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default())
+            .flat_map(|line| line.split(';'))
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .enumerate()
+            .map(|(i, entry)| {
+                parse_step(entry).map_err(|message| ScenarioError::Parse {
+                    step: i + 1,
+                    message,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        steps.sort_by_key(|step| step.time);
+        Ok(Self {
+            steps,
+            next: 0,
+            elapsed: Duration::ZERO,
+            paused: false,
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn restart(&mut self) {
+        self.next = 0;
+        self.elapsed = Duration::ZERO;
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.steps.len()
+    }
+
+    // Jumps the clock to the moment the next step has to be spawned
+    pub fn skip_to_next_step(&mut self, travel: Duration) {
+        if let Some(step) = self.steps.get(self.next) {
+            let spawn_at = step.time.saturating_sub(travel);
+            self.elapsed = self.elapsed.max(spawn_at);
+        }
+    }
+
+    pub fn status(&self) -> String {
+        format!(
+            "Scenario: step {}/{} at {:.1}s{} (Space pause, N step, R rewind)",
+            self.next,
+            self.steps.len(),
+            self.elapsed.as_secs_f32(),
+            if self.paused { ", paused" } else { "" }
+        )
+    }
+}
+
+// Marks the events spawned by the scenario, so a rewind can take them back
+#[derive(Component)]
+pub struct ScenarioEvent;
+
+// Events in flight stop with a paused scenario, true when there is no scenario
+pub fn scenario_running(schedule: Option<Res<ScenarioSchedule>>) -> bool {
+    schedule.is_none_or(|schedule| !schedule.paused)
+}
+
+// Spawns every step early enough for its events to reach the planet at the scripted time
+pub fn run_scenario_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut schedule: ResMut<ScenarioSchedule>,
+    config: Res<GalaxyEventConfig>,
+    mut rng: ResMut<SimulationRng>,
+    planet_query: Query<(Entity, &Name, &PlanetId, &Transform), With<Planet>>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    let schedule = &mut *schedule;
+    if !schedule.paused {
+        schedule.elapsed += time.delta();
+    }
+    let horizon = schedule.elapsed + Duration::from_secs_f32(config.travel_secs);
+
+    while let Some(step) = schedule.steps.get(schedule.next) {
+        if step.time > horizon {
+            break;
+        }
+        schedule.next += 1;
+
+        let Some((target, _, id, transform)) = planet_query
+            .iter()
+            .find(|&(_, name, _, _)| name.as_str() == step.planet)
+        else {
+            log.write(LogEvent::error(format!(
+                "Scenario skipped {:?}: no planet named {}",
+                step.event, step.planet
+            )));
+            continue;
+        };
+        let travel = step.time.saturating_sub(schedule.elapsed);
+        for _ in 0..step.count {
+            let event = match step.event {
+                // The handler ticks the timer in the same frame it is spawned
+                GalaxyEvent::Sunray => commands
+                    .spawn((
+                        DespawnOnExit(GameState::Creative),
+                        GalaxyEvent::Sunray,
+                        EventTarget {
+                            planet: target,
+                            duration: Timer::new(travel + time.delta(), TimerMode::Once),
+                        },
+                    ))
+                    .id(),
+                GalaxyEvent::Asteroid => {
+                    let positions: Vec<Vec2> = planet_query
+                        .iter()
+                        .map(|(_, _, _, transform)| transform.translation.truncate())
+                        .collect();
+                    launch_asteroid(
+                        &mut commands,
+                        &mut rng.0,
+                        galaxy_edge(&positions),
+                        transform.translation.truncate(),
+                        travel.as_secs_f32(),
+                        None,
+                        GameState::Creative,
+                    )
+                }
+            };
+            commands.entity(event).insert(ScenarioEvent);
+        }
+        let kind = match step.event {
+            GalaxyEvent::Sunray => LogKind::Sunray,
+            GalaxyEvent::Asteroid => LogKind::Asteroid,
+        };
+        log.write(LogEvent::planet(
+            kind,
+            id.0,
+            format!(
+                "scenario sends {:?} x{} for t={:.1}s",
+                step.event,
+                step.count,
+                step.time.as_secs_f32()
+            ),
+        ));
+    }
+}
+
+pub fn scenario_controls_system(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut schedule: ResMut<ScenarioSchedule>,
+    config: Res<GalaxyEventConfig>,
+    event_query: Query<Entity, With<ScenarioEvent>>,
+    mut log: MessageWriter<LogEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        schedule.paused = !schedule.paused;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyN) {
+        schedule.paused = true;
+        schedule.skip_to_next_step(Duration::from_secs_f32(config.travel_secs));
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        schedule.restart();
+        for event in event_query.iter() {
+            commands.entity(event).despawn();
+        }
+        log.write(LogEvent::info("Scenario rewound to the start"));
+    }
+}
+
+#[derive(Component)]
+pub struct ScenarioStatus;
+
+pub fn update_scenario_status(
+    schedule: Res<ScenarioSchedule>,
+    mut status: Single<&mut Text, With<ScenarioStatus>>,
+) {
+    let text = schedule.status();
+    if status.0 != text {
+        status.0 = text;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_steps_with_repeat_and_spaced_names() {
+        let step = parse_step("t=2.5 asteroid Big Alpha x3").unwrap();
+        assert_eq!(step.time, Duration::from_secs_f32(2.5));
+        assert!(matches!(step.event, GalaxyEvent::Asteroid));
+        assert_eq!(step.planet, "Big Alpha");
+        assert_eq!(step.count, 3);
+
+        // Without a planet name after it, x3 is the name
+        let step = parse_step("t=1 sunray x3").unwrap();
+        assert_eq!(step.planet, "x3");
+        assert_eq!(step.count, 1);
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        for entry in [
+            "t=-1 sunray Alpha",
+            "t=NaN sunray Alpha",
+            "t=inf sunray Alpha",
+            "t=1e20 sunray Alpha",
+            "2 sunray Alpha",
+        ] {
+            assert!(parse_step(entry).is_err(), "{entry} should not parse");
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_events_and_missing_planets() {
+        assert!(parse_step("t=1 comet Alpha").is_err());
+        assert!(parse_step("t=1 sunray").is_err());
+    }
+
+    #[test]
+    fn schedule_is_sorted_and_reports_the_failing_step() {
+        let schedule =
+            ScenarioSchedule::parse("t=3 sunray Alpha; t=1 asteroid Beta\n# comment\n").unwrap();
+        assert_eq!(schedule.step_count(), 2);
+        assert_eq!(schedule.steps[0].planet, "Beta");
+
+        match ScenarioSchedule::parse("t=1 sunray Alpha; t=oops sunray Alpha") {
+            Err(ScenarioError::Parse { step, .. }) => assert_eq!(step, 2),
+            _ => panic!("the second step is invalid"),
+        }
+    }
+}
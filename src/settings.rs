@@ -1,15 +1,88 @@
 use super::GameState;
+use crate::explorer::strategy::{ExplorerRoster, StrategyKind};
+use crate::galaxy::{Galaxy, GalaxyHandles};
+use crate::galaxy_event::config::GalaxyEventConfig;
 use bevy::prelude::*;
 
 #[derive(Component)]
 struct SettingsDialog;
 
+// Index of the event setting changed by the arrow keys, in GalaxyEventConfig::tunables order
+#[derive(Resource, Default)]
+struct SelectedTunable(usize);
+
 pub fn settings_plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::Settings), setup)
-        .add_systems(Update, (reset_game.run_if(in_state(GameState::Settings)),));
+    app.init_resource::<SelectedTunable>()
+        .add_systems(OnEnter(GameState::Settings), setup)
+        .add_systems(
+            Update,
+            (
+                reset_game,
+                choose_explorer_strategy,
+                tune_galaxy_events,
+                update_settings_text.run_if(
+                    resource_changed::<ExplorerRoster>
+                        .or(resource_changed::<GalaxyEventConfig>)
+                        .or(resource_changed::<SelectedTunable>)
+                        .or(on_message::<AssetEvent<Galaxy>>),
+                ),
+            )
+                .chain()
+                .run_if(in_state(GameState::Settings)),
+        );
+}
+
+// Shortest spawn interval reachable from the settings screen
+const MIN_SPAWN_INTERVAL_SECS: f32 = 0.25;
+const TUNABLE_STEP: f32 = 0.25;
+
+// Planets of the Playing galaxy, empty until it is loaded
+fn planet_names(galaxies: &Assets<Galaxy>, galaxy_handles: &GalaxyHandles) -> Vec<String> {
+    galaxies
+        .get(galaxy_handles.get(GameState::Playing))
+        .map(|galaxy| {
+            galaxy
+                .planets
+                .iter()
+                .map(|planet| planet.name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
-fn setup(mut commands: Commands) {
+fn settings_text(
+    roster: &ExplorerRoster,
+    events: &GalaxyEventConfig,
+    planets: &[String],
+    selected: &SelectedTunable,
+) -> String {
+    let difficulty = events.difficulty;
+    let tunables: String = events
+        .tunable_values(planets)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (label, value))| {
+            let cursor = if i == selected.0 { ">" } else { " " };
+            format!("{cursor} {label}: {value:.2}\n")
+        })
+        .collect();
+    format!(
+        "Press R to restart\nExplorers: {roster}\n\
+         E changes the last explorer, N adds one, Backspace removes the last one\n\
+         Difficulty: {difficulty:?}, D changes it\n\
+         Up and Down select an event setting, Left and Right change it\n{tunables}"
+    )
+}
+
+fn setup(
+    mut commands: Commands,
+    roster: Res<ExplorerRoster>,
+    events: Res<GalaxyEventConfig>,
+    selected: Res<SelectedTunable>,
+    galaxies: Res<Assets<Galaxy>>,
+    galaxy_handles: Res<GalaxyHandles>,
+) {
+    let planets = planet_names(&galaxies, &galaxy_handles);
     commands.spawn((
         Node {
             position_type: PositionType::Absolute,
@@ -22,7 +95,7 @@ fn setup(mut commands: Commands) {
         },
         DespawnOnExit(GameState::Settings),
         BackgroundColor(Color::BLACK.with_alpha(0.7)),
-        Text::new("Press R to restart"),
+        Text::new(settings_text(&roster, &events, &planets, &selected)),
         TextFont::default().with_font_size(16.0),
         TextColor(Color::WHITE),
         SettingsDialog,
@@ -34,12 +107,86 @@ fn reset_game(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut next_state: ResMut<NextState<GameState>>,
     current_state: Res<State<GameState>>,
+    asset_server: Res<AssetServer>,
+    galaxy_handles: Res<GalaxyHandles>,
 ) {
-    if keyboard_input.pressed(KeyCode::KeyR) {
+    let galaxy_ready =
+        |state: GameState| asset_server.is_loaded_with_dependencies(galaxy_handles.get(state));
+
+    if keyboard_input.pressed(KeyCode::KeyR) && galaxy_ready(GameState::Playing) {
         next_state.set(GameState::Playing);
     }
 
-    if keyboard_input.pressed(KeyCode::KeyC) {
+    if keyboard_input.pressed(KeyCode::KeyC) && galaxy_ready(GameState::Creative) {
         next_state.set(GameState::Creative);
     }
 }
+
+fn choose_explorer_strategy(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut roster: ResMut<ExplorerRoster>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyE)
+        && let Some(last) = roster.0.last_mut()
+    {
+        *last = last.next();
+    } else if keyboard_input.just_pressed(KeyCode::KeyN) {
+        roster.0.push(StrategyKind::RoundRobin);
+    } else if keyboard_input.just_pressed(KeyCode::Backspace) && roster.0.len() > 1 {
+        roster.0.pop();
+    }
+}
+
+fn tune_galaxy_events(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut events: ResMut<GalaxyEventConfig>,
+    mut selected: ResMut<SelectedTunable>,
+    galaxies: Res<Assets<Galaxy>>,
+    galaxy_handles: Res<GalaxyHandles>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyD) {
+        events.difficulty = events.difficulty.next();
+        return;
+    }
+
+    let planets = planet_names(&galaxies, &galaxy_handles);
+    let count = events.tunable_values(&planets).len();
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        selected.0 = (selected.0 + count - 1) % count;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        selected.0 = (selected.0 + 1) % count;
+    }
+    // The planet list can shrink if the galaxy is reloaded
+    if selected.0 >= count {
+        selected.0 = 0;
+    }
+
+    let step = if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        -TUNABLE_STEP
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        TUNABLE_STEP
+    } else {
+        return;
+    };
+    // Only the spawn interval, listed first, must stay above zero
+    let min = if selected.0 == 0 {
+        MIN_SPAWN_INTERVAL_SECS
+    } else {
+        0.0
+    };
+    if let Some(value) = events.tunable_mut(selected.0, &planets) {
+        *value = (*value + step).max(min);
+    }
+}
+
+fn update_settings_text(
+    mut dialog: Single<&mut Text, With<SettingsDialog>>,
+    roster: Res<ExplorerRoster>,
+    events: Res<GalaxyEventConfig>,
+    selected: Res<SelectedTunable>,
+    galaxies: Res<Assets<Galaxy>>,
+    galaxy_handles: Res<GalaxyHandles>,
+) {
+    let planets = planet_names(&galaxies, &galaxy_handles);
+    dialog.0 = settings_text(&roster, &events, &planets, &selected);
+}
@@ -1,24 +1,59 @@
 use crate::EventSpawnTimer;
 use crate::GameState;
+use crate::clock::SimulationClock;
+use crate::explorer::crafting::crafting_panel;
+use crate::explorer::dialog::{land_on_planet_dialog, landed_planet_dialog};
+use crate::explorer::inventory::inventory_panel;
+use crate::explorer::strategy::{Autopilot, ExplorerRoster};
+use crate::explorer::{Selected, explorer};
+use crate::galaxy::*;
+use crate::galaxy_event::config::GalaxyEventConfig;
 use crate::galaxy_event::*;
-use crate::orchestrator::Orchestrator;
+use crate::log::*;
+use crate::orchestrator::factory::PlanetRegistry;
+use crate::orchestrator::lifecycle::*;
+use crate::orchestrator::pending::*;
+use crate::orchestrator::record::RecordPath;
+use crate::orchestrator::{
+    Orchestrator, OrchestratorError, PlanetReply, planet_backlog_diagnostic,
+};
+use crate::planet::inspector::{AiStatus, PlanetReport};
 use crate::planet::*;
+use crate::replay::*;
+use crate::resources::{PlanetMessageBudget, SimulationRng, SimulationSeed};
+use crate::stats::*;
 use bevy::prelude::*;
 use common_game::protocols::orchestrator_planet::*;
 use common_game::protocols::planet_explorer::*;
-use crossbeam_channel::*;
 
 pub fn simulation_better_plugin(app: &mut App) {
     app.add_systems(OnEnter(GameState::Playing), setup)
         .init_resource::<EventSpawnTimer>()
+        .init_resource::<GalaxyEventConfig>()
+        .add_systems(
+            FixedUpdate,
+            (
+                crate::galaxy_event::event_spawner_system
+                    .run_if(not(resource_exists::<ReplaySchedule>)),
+                replay_events_system.run_if(resource_exists::<ReplaySchedule>),
+                deliver_recorded_hits_system,
+                crate::galaxy_event::event_handler_system,
+                crate::galaxy_event::trajectory::asteroid_motion_system,
+                crate::galaxy_event::cleanup_events_system,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
         .add_systems(
             Update,
             (
-                crate::galaxy_event::event_spawner_system,
                 crate::galaxy_event::event_visual_move,
-                crate::galaxy_event::event_handler_system,
+                crate::galaxy_event::phenomenon::black_hole_system,
+                planet_backlog_diagnostic,
                 listen_to_planets,
-                crate::galaxy_event::cleanup_events_system,
+                expire_pending_requests,
+                despawn_destroyed_planets,
+                join_killed_planets,
             )
                 .chain()
                 .run_if(in_state(GameState::Playing)),
@@ -32,228 +67,288 @@ pub fn simulation_better_plugin(app: &mut App) {
             )
                 .run_if(in_state(GameState::Playing)),
         )
-        .add_observer(event_visual_spawn);
+        .add_observer(event_visual_spawn)
+        .add_observer(crate::galaxy_event::phenomenon::black_hole_visual)
+        .add_observer(crate::galaxy_event::trajectory::asteroid_body_visual);
 }
 
-pub fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let mut orchestrator = Orchestrator::new();
-    let mut id = 0;
-
-    let (orch_tx, orch_rx) = unbounded();
-    let (planet_tx, planet_rx) = unbounded();
-    let (_expl_tx, expl_rx) = unbounded();
-    orchestrator.add_op_tx(id, orch_tx);
-    orchestrator.add_po_rx(id, planet_rx);
-    let mut p1 = trip::trip(id, orch_rx, planet_tx, expl_rx).expect("Error creating planet1");
-    let planet1 = commands
-        .spawn(planet(
-            id,
-            "Alpha",
-            Vec3::new(400.0, 0.0, 0.0),
-            asset_server.load("sprites/Ice.png"),
-        ))
-        .id();
-    let p1_handle = std::thread::spawn(move || {
-        let _ = p1.run();
-    });
-    orchestrator.add_planet_handle(id, p1_handle);
-
-    id += 1;
-    let (orch_tx, orch_rx) = unbounded();
-    let (planet_tx, planet_rx) = unbounded();
-    let (_expl_tx, expl_rx) = unbounded();
-    orchestrator.add_op_tx(id, orch_tx);
-    orchestrator.add_po_rx(id, planet_rx);
-    let mut p2 = trip::trip(id, orch_rx, planet_tx, expl_rx).expect("Error creating planet1");
-    let planet2 = commands
-        .spawn(planet(
-            id,
-            "Beta",
-            Vec3::new(0.0, 0.0, 0.0),
-            asset_server.load("sprites/Terran.png"),
-        ))
-        .id();
-    let p2_handle = std::thread::spawn(move || {
-        let _ = p2.run();
-    });
-    orchestrator.add_planet_handle(id, p2_handle);
-
-    id += 1;
-    let (orch_tx, orch_rx) = unbounded();
-    let (planet_tx, planet_rx) = unbounded();
-    let (_expl_tx, expl_rx) = unbounded();
-    orchestrator.add_op_tx(id, orch_tx);
-    orchestrator.add_po_rx(id, planet_rx);
-    let mut p3 = trip::trip(id, orch_rx, planet_tx, expl_rx).expect("Error creating planet1");
-    let planet3 = commands
-        .spawn(planet(
-            id,
-            "Gamma",
-            Vec3::new(0.0, 200.0, 0.0),
-            asset_server.load("sprites/Terran.png"),
-        ))
-        .id();
-    let p3_handle = std::thread::spawn(move || {
-        let _ = p3.run();
-    });
-    orchestrator.add_planet_handle(id, p3_handle);
-
-    commands.spawn((
-        Node {
-            flex_direction: FlexDirection::Column,
-            row_gap: percent(5.0),
-            left: px(20),
-            height: percent(90.0),
-            width: percent(20.0),
-            top: percent(5.0),
-            ..default()
-        },
-        children![
-            (planet_state(
-                &asset_server,
-                "Alpha",
-                planet1,
-                PlanetCell {
-                    num_cell: 5,
-                    charged_cell: 0,
-                },
-                PlanetRocket(false),
-            )),
-            (planet_state(
-                &asset_server,
-                "Beta",
-                planet2,
-                PlanetCell {
-                    num_cell: 5,
-                    charged_cell: 0,
-                },
-                PlanetRocket(false),
-            )),
-            (planet_state(
-                &asset_server,
-                "Gamma",
-                planet3,
-                PlanetCell {
-                    num_cell: 5,
-                    charged_cell: 0,
-                },
-                PlanetRocket(false),
-            )),
-        ],
-    ));
+pub fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    galaxies: Res<Assets<Galaxy>>,
+    galaxy_handles: Res<GalaxyHandles>,
+    registry: Res<PlanetRegistry>,
+    seed: Res<SimulationSeed>,
+    record: Option<Res<RecordPath>>,
+    replay: Option<ResMut<ReplaySchedule>>,
+    roster: Res<ExplorerRoster>,
+    event_config: Res<GalaxyEventConfig>,
+) {
+    let Some(galaxy) = galaxies.get(galaxy_handles.get(GameState::Playing)) else {
+        error!("Galaxy definition for the Playing state is not loaded");
+        return;
+    };
+    let orchestrator = spawn_galaxy(
+        &mut commands,
+        &asset_server,
+        &registry,
+        galaxy,
+        record.as_deref(),
+        GameState::Playing,
+    );
 
-    commands.insert_resource(EventSpawnTimer(Timer::from_seconds(
-        1.0,
-        TimerMode::Repeating,
-    )));
+    commands.spawn(log_screen(GameState::Playing));
 
-    for i in 0..=id {
-        orchestrator.send_to_planet_id(i, OrchestratorToPlanet::StartPlanetAI);
-        match orchestrator
-            .recv_from_planet_id(i)
-            .expect("No message received")
-        {
-            PlanetToOrchestrator::StartPlanetAIResult { planet_id } => {
-                info!("Planet {planet_id} started");
+    // The explorers start in a row just below the first planet of the galaxy
+    let start = galaxy.planets.first().map_or(Vec2::ZERO, |description| {
+        description.translation().truncate()
+    }) - Vec2::new(0.0, 120.0);
+    let mut selected = false;
+    for (id, kind) in (0u32..).zip(roster.0.iter()) {
+        let position = start + Vec2::new(60.0 * id as f32, 0.0);
+        let explorer = commands
+            .spawn(explorer(&asset_server, id, position, GameState::Playing))
+            .id();
+        match kind.build() {
+            Some(strategy) => {
+                commands.write_message(LogEvent::info(format!(
+                    "Explorer {id} strategy: {}",
+                    strategy.name()
+                )));
+                commands.entity(explorer).insert(Autopilot::new(strategy));
+            }
+            None if !selected => {
+                selected = true;
+                commands.entity(explorer).insert(Selected);
             }
-            _other => panic!("Failed to start planet"),
+            None => {}
         }
     }
+    commands.spawn(land_on_planet_dialog(&asset_server, GameState::Playing));
+    commands.spawn(landed_planet_dialog(&asset_server, GameState::Playing));
+    commands.spawn(inventory_panel(&asset_server, GameState::Playing));
+    commands.spawn(crafting_panel(&asset_server, GameState::Playing));
+
+    info!("Simulation seed: {}", seed.0);
+    commands.write_message(LogEvent::info(format!("Simulation seed: {}", seed.0)));
+    commands.insert_resource(SimulationRng::from_seed(*seed));
+    if let Some(mut replay) = replay {
+        replay.restart();
+        commands.write_message(LogEvent::info(format!(
+            "Replaying {} recorded galaxy events",
+            replay.event_count()
+        )));
+    }
+    commands.insert_resource(GalaxyStats::from_galaxy(galaxy));
+    commands.write_message(LogEvent::info(event_config.summary()));
+
+    commands.insert_resource(EventSpawnTimer::new(event_config.spawn_interval_secs));
 
     commands.insert_resource(orchestrator);
 }
 
 pub fn listen_to_planets(
-    mut commands: Commands,
-    orch: Res<Orchestrator>,
+    mut orch: ResMut<Orchestrator>,
+    budget: Res<PlanetMessageBudget>,
+    mut destroyed: MessageWriter<PlanetDestroyed>,
+    mut killed: MessageWriter<PlanetKilled>,
+    mut log: MessageWriter<LogEvent>,
+    mut sunrays: MessageWriter<SunrayAcked>,
+    mut deflected: MessageWriter<AsteroidDeflected>,
+    mut transits: MessageWriter<ExplorerTransit>,
     planet_query: Query<(&PlanetId, Entity), With<Planet>>,
     ui_query: Query<(Entity, &PlanetUi)>,
     children_query: Query<&Children, With<PlanetUi>>,
     mut cell_query: Query<&mut PlanetCell>,
     mut rocket_query: Query<&mut PlanetRocket>,
+    mut report_query: Query<&mut PlanetReport>,
+    clock: Option<Res<SimulationClock>>,
 ) {
-    for rx in orch.planet_rxs() {
-        match rx.try_recv() {
-            Ok(msg) => match msg {
-                PlanetToOrchestrator::SunrayAck { planet_id } => {
-                    orch.send_to_planet_id(planet_id, OrchestratorToPlanet::InternalStateRequest);
-                    info!("Sunray received by {planet_id}");
+    for reply in orch.drain_planets(budget.0) {
+        let PlanetReply {
+            planet_id: from,
+            msg,
+            request,
+        } = match reply {
+            Ok(reply) => reply,
+            Err(OrchestratorError::Disconnected(planet_id)) => {
+                log.write(LogEvent::planet(
+                    LogKind::Error,
+                    planet_id,
+                    "channel got disconnected",
+                ));
+                killed.write(PlanetKilled { planet_id });
+                continue;
+            }
+            Err(e) => {
+                log.write(LogEvent::error(e.to_string()));
+                continue;
+            }
+        };
+        match &request {
+            Some(request) => debug!(
+                "planet {from} answered request {:?} after {:?}",
+                request.id,
+                request.sent_at.elapsed()
+            ),
+            None => {
+                let e = OrchestratorError::UnexpectedReply {
+                    planet_id: from,
+                    reply: format!("{msg:?}"),
+                };
+                warn!("{e}");
+                log.write(LogEvent::error(e.to_string()));
+            }
+        }
+
+        match msg {
+            PlanetToOrchestrator::SunrayAck { planet_id } => {
+                sunrays.write(SunrayAcked { planet_id });
+                log.write(LogEvent::planet(
+                    LogKind::Sunray,
+                    planet_id,
+                    "absorbed a sunray",
+                ));
+                info!("Sunray received by {planet_id}");
+            }
+            PlanetToOrchestrator::AsteroidAck { planet_id, rocket } => match rocket {
+                Some(_) => {
+                    deflected.write(AsteroidDeflected { planet_id });
+                    log.write(LogEvent::planet(
+                        LogKind::Deflected,
+                        planet_id,
+                        "deflected an asteroid with a rocket 󱎯",
+                    ));
+                    info!(" Asteroid approaching planet {planet_id} Was destroyed by a rocket 󱎯",);
                 }
-                PlanetToOrchestrator::AsteroidAck { planet_id, rocket } => match rocket {
-                    Some(_) => {
-                        info!(
-                            " Asteroid approaching planet {planet_id} Was destroyed by a rocket 󱎯",
-                        );
-                        orch.send_to_planet_id(
-                            planet_id,
-                            OrchestratorToPlanet::InternalStateRequest,
-                        );
-                    }
-                    None => {
-                        let Some((id, planet_entity)) =
-                            planet_query.iter().find(|&(id, _)| id.0 == planet_id)
-                        else {
-                            return;
-                        };
-                        let Some((entity, _)) =
-                            ui_query.iter().find(|&(_, ui)| ui.0 == planet_entity)
-                        else {
-                            return;
-                        };
-                        commands.entity(planet_entity).despawn();
-                        commands.entity(entity).despawn();
-                        orch.send_to_planet_id(planet_id, OrchestratorToPlanet::KillPlanet);
-                    }
-                },
-                PlanetToOrchestrator::StartPlanetAIResult { planet_id } => {}
-                PlanetToOrchestrator::StopPlanetAIResult { planet_id } => {}
-                PlanetToOrchestrator::KillPlanetResult { planet_id } => {
-                    //TODO; send an event to join the planet thread
-                    //  orch.join_planet_id(planet_id);
-                    info!("planet {planet_id} killed successfully");
+                None => {
+                    log.write(LogEvent::planet(
+                        LogKind::Destroyed,
+                        planet_id,
+                        "was destroyed by an asteroid",
+                    ));
+                    destroyed.write(PlanetDestroyed { planet_id });
+                }
+            },
+            PlanetToOrchestrator::StartPlanetAIResult { planet_id } => {
+                set_ai_status(
+                    &planet_query,
+                    &mut report_query,
+                    planet_id,
+                    AiStatus::Started,
+                );
+                // The first state report, polling keeps it fresh afterwards. A paused
+                // clock only starts the planets for a step, which asks for the state itself
+                let paused = clock.as_ref().is_some_and(|clock| clock.paused);
+                if !paused
+                    && let Err(e) = orch
+                        .send_to_planet_id(planet_id, OrchestratorToPlanet::InternalStateRequest)
+                {
+                    log.write(LogEvent::error(e.to_string()));
                 }
-                PlanetToOrchestrator::InternalStateResponse {
+                info!("Planet {planet_id} started");
+            }
+            PlanetToOrchestrator::StopPlanetAIResult { planet_id } => {
+                set_ai_status(
+                    &planet_query,
+                    &mut report_query,
                     planet_id,
-                    planet_state,
-                } => {
-                    let Some((id, planet_entity)) =
-                        planet_query.iter().find(|&(id, _)| id.0 == planet_id)
-                    else {
-                        return;
-                    };
-                    let Some((entity, _)) = ui_query.iter().find(|&(_, ui)| ui.0 == planet_entity)
-                    else {
-                        return;
-                    };
-                    let Ok(children) = children_query.get(entity) else {
-                        return;
-                    };
+                    AiStatus::Stopped,
+                );
+                info!("Planet {planet_id} stopped");
+            }
+            PlanetToOrchestrator::KillPlanetResult { planet_id } => {
+                killed.write(PlanetKilled { planet_id });
+                info!("planet {planet_id} killed successfully");
+            }
+            PlanetToOrchestrator::InternalStateResponse {
+                planet_id,
+                planet_state,
+            } => {
+                let Some((_, planet_entity)) =
+                    planet_query.iter().find(|&(id, _)| id.0 == planet_id)
+                else {
+                    continue;
+                };
+                if let Ok(mut report) = report_query.get_mut(planet_entity) {
+                    report.record_state(
+                        planet_state.energy_cells.clone(),
+                        planet_state.charged_cells_count,
+                        planet_state.has_rocket,
+                    );
+                }
+                let Some((entity, _)) = ui_query.iter().find(|&(_, ui)| ui.0 == planet_entity)
+                else {
+                    continue;
+                };
+                let Ok(children) = children_query.get(entity) else {
+                    continue;
+                };
 
-                    for child in children.iter() {
-                        if let Ok(mut cell) = cell_query.get_mut(child) {
-                            cell.num_cell = planet_state.energy_cells.len();
-                            cell.charged_cell = planet_state.charged_cells_count;
-                        }
-                        if let Ok(mut rocket) = rocket_query.get_mut(child) {
-                            rocket.0 = planet_state.has_rocket;
-                        }
+                for child in children.iter() {
+                    if let Ok(mut cell) = cell_query.get_mut(child) {
+                        cell.num_cell = planet_state.energy_cells.len();
+                        cell.charged_cell = planet_state.charged_cells_count;
+                    }
+                    if let Ok(mut rocket) = rocket_query.get_mut(child) {
+                        rocket.0 = planet_state.has_rocket;
                     }
                 }
-                PlanetToOrchestrator::IncomingExplorerResponse {
+            }
+            PlanetToOrchestrator::IncomingExplorerResponse {
+                planet_id,
+                res,
+                explorer_id,
+            } => {
+                transits.write(ExplorerTransit {
                     planet_id,
-                    res,
                     explorer_id,
-                } => {}
-                PlanetToOrchestrator::OutgoingExplorerResponse {
-                    planet_id,
+                    direction: Transit::Incoming,
                     res,
+                });
+            }
+            PlanetToOrchestrator::OutgoingExplorerResponse {
+                planet_id,
+                res,
+                explorer_id,
+            } => {
+                transits.write(ExplorerTransit {
+                    planet_id,
                     explorer_id,
-                } => {}
-                PlanetToOrchestrator::Stopped { planet_id } => {}
-            },
-            Err(TryRecvError::Empty) => {}
-            Err(TryRecvError::Disconnected) => {}
-        };
+                    direction: Transit::Outgoing,
+                    res,
+                });
+            }
+            PlanetToOrchestrator::Stopped { planet_id } => {
+                set_ai_status(
+                    &planet_query,
+                    &mut report_query,
+                    planet_id,
+                    AiStatus::Stopped,
+                );
+                if let Some(request) = &request {
+                    log.write(LogEvent::planet(
+                        LogKind::Info,
+                        planet_id,
+                        format!("is stopped and refused {:?}", request.kind),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn set_ai_status(
+    planet_query: &Query<(&PlanetId, Entity), With<Planet>>,
+    report_query: &mut Query<&mut PlanetReport>,
+    planet_id: u32,
+    status: AiStatus,
+) {
+    let Some((_, planet)) = planet_query.iter().find(|&(id, _)| id.0 == planet_id) else {
+        return;
+    };
+    if let Ok(mut report) = report_query.get_mut(planet) {
+        report.ai = status;
     }
 }
 
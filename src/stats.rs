@@ -0,0 +1,101 @@
+use crate::galaxy::Galaxy;
+use crate::orchestrator::lifecycle::PlanetDestroyed;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+#[derive(Message)]
+pub struct SunrayAcked {
+    pub planet_id: u32,
+}
+
+#[derive(Message)]
+pub struct AsteroidDeflected {
+    pub planet_id: u32,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PlanetStats {
+    pub name: String,
+    pub ai: String,
+    pub sunrays: u32,
+    pub deflected: u32,
+    pub fatal: u32,
+    pub died_at: Option<f32>,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct GalaxyStats {
+    pub planets: BTreeMap<u32, PlanetStats>,
+}
+
+impl GalaxyStats {
+    pub fn from_galaxy(galaxy: &Galaxy) -> Self {
+        let planets = (0u32..)
+            .zip(galaxy.planets.iter())
+            .map(|(id, description)| {
+                (
+                    id,
+                    PlanetStats {
+                        name: description.name.clone(),
+                        ai: description.ai.clone(),
+                        ..default()
+                    },
+                )
+            })
+            .collect();
+        Self { planets }
+    }
+
+    pub fn summary(&self, elapsed: f32) -> String {
+        let mut summary = format!(
+            "{:<12} {:<10} {:>12} {:>8} {:>10} {:>6}\n",
+            "planet", "ai", "survived(s)", "sunrays", "deflected", "fatal"
+        );
+        for stats in self.planets.values() {
+            let _ = writeln!(
+                summary,
+                "{:<12} {:<10} {:>12.2} {:>8} {:>10} {:>6}",
+                stats.name,
+                stats.ai,
+                stats.died_at.unwrap_or(elapsed),
+                stats.sunrays,
+                stats.deflected,
+                stats.fatal
+            );
+        }
+        summary
+    }
+}
+
+pub fn stats_plugin(app: &mut App) {
+    app.add_message::<SunrayAcked>()
+        .add_message::<AsteroidDeflected>()
+        .init_resource::<GalaxyStats>()
+        .add_systems(Update, record_stats);
+}
+
+fn record_stats(
+    time: Res<Time>,
+    mut stats: ResMut<GalaxyStats>,
+    mut sunrays: MessageReader<SunrayAcked>,
+    mut deflected: MessageReader<AsteroidDeflected>,
+    mut destroyed: MessageReader<PlanetDestroyed>,
+) {
+    for SunrayAcked { planet_id } in sunrays.read() {
+        if let Some(planet) = stats.planets.get_mut(planet_id) {
+            planet.sunrays += 1;
+        }
+    }
+    for AsteroidDeflected { planet_id } in deflected.read() {
+        if let Some(planet) = stats.planets.get_mut(planet_id) {
+            planet.deflected += 1;
+        }
+    }
+    for PlanetDestroyed { planet_id } in destroyed.read() {
+        if let Some(planet) = stats.planets.get_mut(planet_id) {
+            planet.fatal += 1;
+            planet.died_at.get_or_insert(time.elapsed_secs());
+        }
+    }
+}
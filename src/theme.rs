@@ -12,6 +12,28 @@ pub mod color {
     use bevy::prelude::*;
     pub const TEXT: Color = Color::WHITE;
     pub const BACKGROUND: Color = Color::BLACK;
+    pub const MUTED: Color = Color::srgb(0.6, 0.6, 0.6);
+
+    pub const SUNRAY: Color = Color::srgb(1.0, 0.9, 0.3);
+    pub const ASTEROID: Color = Color::srgb(0.7, 0.6, 0.5);
+    pub const DEFLECTED: Color = Color::srgb(0.4, 0.8, 1.0);
+    pub const DESTROYED: Color = Color::srgb(1.0, 0.3, 0.3);
+    pub const ERROR: Color = Color::srgb(1.0, 0.6, 0.2);
+    pub const SELECTED: Color = Color::srgb(1.0, 0.85, 0.4);
+
+    // Cycled through by planet id
+    pub const PLANETS: [Color; 6] = [
+        Color::srgb(0.5, 0.9, 0.5),
+        Color::srgb(0.9, 0.5, 0.9),
+        Color::srgb(0.5, 0.7, 1.0),
+        Color::srgb(1.0, 0.7, 0.4),
+        Color::srgb(0.4, 0.9, 0.9),
+        Color::srgb(0.9, 0.9, 0.5),
+    ];
+
+    pub fn planet(id: u32) -> Color {
+        PLANETS[id as usize % PLANETS.len()]
+    }
 }
 
 pub fn title_font(asset_server: &Res<AssetServer>) -> TextFont {